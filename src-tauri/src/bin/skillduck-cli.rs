@@ -0,0 +1,386 @@
+//! Headless command-line interface for SkillDuck.
+//!
+//! Uses the same `skillduck_lib::core` functions the GUI commands wrap and
+//! prints JSON, so the scanner and validator can be used from CI and scripts.
+
+use serde::Serialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use skillduck_lib::core::skills::ScopeFilter;
+use skillduck_lib::core::{agents, cli_bridge, editor, logging, paths, settings, skill_tests, skills, sync, trigger, Error};
+
+const USAGE: &str = "\
+Usage: skillduck-cli <command> [options]
+
+Commands:
+  list      [--scope all|global|project] [--home <dir>]...
+                                                List installed skills
+  agents    [--scan-root <dir>]... [--home <dir>]...
+                                                Detect agents and their skills
+  validate  <path>...                           Validate SKILL.md files or skill directories
+  lint      [--scope all|global|project] [--home <dir>]... [--strict]
+                                                Validate every installed skill
+  sync      --from <agent> [--to <agent>[,<agent>...]] [--project <dir>] [--dry-run]
+                                                Copy skills from one agent to others
+  test      <path>... [--junit]                 Run the test cases in each skill's tests/
+                                                (--junit prints JUnit XML instead of JSON)
+  trigger   <prompt> --agent <agent> [--project <dir>]
+                                                Rank the agent's skills by how well they match a prompt
+  doctor                                        Check the local environment
+
+Options:
+  --compact   Print JSON on a single line
+  -h, --help  Show this help
+";
+
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+    compact: bool,
+}
+
+impl Args {
+    fn parse(raw: Vec<String>) -> Result<Args, String> {
+        let mut iter = raw.into_iter();
+        let command = iter.next().ok_or("Missing command")?;
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut compact = false;
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--compact" => compact = true,
+                "--dry-run" | "--strict" | "--junit" => options.push((arg, None)),
+                _ if arg.starts_with("--") => {
+                    let (key, value) = match arg.split_once('=') {
+                        Some((k, v)) => (k.to_string(), v.to_string()),
+                        None => {
+                            let value = iter.next().ok_or_else(|| format!("Missing value for {}", arg))?;
+                            (arg, value)
+                        }
+                    };
+                    options.push((key, Some(value)));
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        Ok(Args {
+            command,
+            positional,
+            options,
+            compact,
+        })
+    }
+
+    fn value(&self, key: &str) -> Option<String> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.clone())
+    }
+
+    fn values(&self, key: &str) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|(k, _)| k == key)
+            .filter_map(|(_, v)| v.clone())
+            .collect()
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.options.iter().any(|(k, _)| k == key)
+    }
+}
+
+fn print_json<T: Serialize>(value: &T, compact: bool) {
+    let out = if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    };
+    println!("{}", out.unwrap_or_else(|e| json!({ "error": e.to_string() }).to_string()));
+}
+
+/// Home roots given with `--home`, or `None` to use the configured ones.
+fn homes_arg(args: &Args) -> Option<Vec<String>> {
+    let homes = args.values("--home");
+    if homes.is_empty() { None } else { Some(homes) }
+}
+
+fn scope_arg(args: &Args) -> Result<ScopeFilter, Error> {
+    let scope = args.value("--scope").unwrap_or_else(|| "all".to_string());
+    ScopeFilter::parse(&scope)
+}
+
+/// Accepts either a SKILL.md path or the skill directory containing it.
+fn skill_md_path(path: &str) -> String {
+    let p = Path::new(path);
+    if p.is_dir() {
+        p.join("SKILL.md").to_string_lossy().to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn cmd_list(args: &Args) -> Result<bool, Error> {
+    let skills = skills::list_skills(scope_arg(args)?, homes_arg(args))?;
+    print_json(&skills, args.compact);
+    Ok(true)
+}
+
+fn cmd_agents(args: &Args) -> Result<bool, Error> {
+    let roots = args.values("--scan-root");
+    let scan_roots = if roots.is_empty() { None } else { Some(roots) };
+    let agents = agents::detect_agents(scan_roots, homes_arg(args))?;
+    print_json(&agents, args.compact);
+    Ok(true)
+}
+
+fn cmd_validate(args: &Args) -> Result<bool, Error> {
+    if args.positional.is_empty() {
+        return Err(Error::InvalidInput("validate requires at least one path".into()));
+    }
+
+    let mut all_valid = true;
+    let mut results = Vec::new();
+    for path in &args.positional {
+        let path = skill_md_path(path);
+        let result = editor::validate_skill(&path)?;
+        all_valid &= result.valid;
+        results.push(json!({ "path": path, "result": result }));
+    }
+
+    print_json(&results, args.compact);
+    Ok(all_valid)
+}
+
+fn cmd_lint(args: &Args) -> Result<bool, Error> {
+    let strict = args.flag("--strict");
+    let skills = skills::list_skills(scope_arg(args)?, homes_arg(args))?;
+
+    let mut passed = true;
+    let mut error_count = 0;
+    let mut warning_count = 0;
+    let mut results = Vec::new();
+    for skill in &skills {
+        let result = editor::validate_skill(&skill.install_path)?;
+        error_count += result.errors.len();
+        warning_count += result.warnings.len();
+        if !result.valid || (strict && !result.warnings.is_empty()) {
+            passed = false;
+        }
+        results.push(json!({
+            "name": skill.name,
+            "path": skill.install_path,
+            "scope": skill.scope,
+            "agents": skill.agents,
+            "result": result,
+        }));
+    }
+
+    print_json(
+        &json!({
+            "passed": passed,
+            "skills": skills.len(),
+            "errors": error_count,
+            "warnings": warning_count,
+            "results": results,
+        }),
+        args.compact,
+    );
+    Ok(passed)
+}
+
+fn cmd_sync(args: &Args) -> Result<bool, Error> {
+    let from = args
+        .value("--from")
+        .ok_or_else(|| Error::InvalidInput("sync requires --from <agent>".into()))?;
+    let to: Vec<String> = args
+        .values("--to")
+        .iter()
+        .flat_map(|v| v.split(','))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let project = args.value("--project");
+    let global = project.is_none();
+    // For a project, the agents may come from its .skillduck.toml instead.
+    if to.is_empty() && global {
+        return Err(Error::InvalidInput("sync requires --to <agent>".into()));
+    }
+
+    let report = sync::sync_skills(&from, &to, global, project.as_deref(), args.flag("--dry-run"))?;
+    print_json(&report, args.compact);
+    Ok(true)
+}
+
+fn cmd_test(args: &Args) -> Result<bool, Error> {
+    if args.positional.is_empty() {
+        return Err(Error::InvalidInput("test requires at least one skill path".into()));
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| Error::Internal(format!("Failed to start the async runtime: {}", e)))?;
+    let mut reports = Vec::new();
+    for path in &args.positional {
+        reports.push(runtime.block_on(skill_tests::run_skill_tests(path))?);
+    }
+
+    if args.flag("--junit") {
+        print!("{}", skill_tests::junit_xml(&reports));
+    } else {
+        print_json(&reports, args.compact);
+    }
+    Ok(reports.iter().all(|r| r.failed == 0))
+}
+
+fn cmd_trigger(args: &Args) -> Result<bool, Error> {
+    let prompt = args.positional.join(" ");
+    let agent = args
+        .value("--agent")
+        .ok_or_else(|| Error::InvalidInput("trigger requires --agent <agent>".into()))?;
+    let report = trigger::simulate_trigger(&prompt, &agent, args.value("--project").as_deref())?;
+    print_json(&report, args.compact);
+    Ok(true)
+}
+
+fn check(name: &str, status: &str, message: String) -> serde_json::Value {
+    json!({ "name": name, "status": status, "message": message })
+}
+
+fn cmd_doctor(args: &Args) -> Result<bool, Error> {
+    let mut checks = Vec::new();
+
+    match dirs::home_dir() {
+        Some(home) => checks.push(check("home", "ok", home.to_string_lossy().to_string())),
+        None => checks.push(check("home", "error", "Cannot determine home directory".to_string())),
+    }
+
+    // Doctor only looks: the legacy migration and a corrupt settings file are
+    // reported, and left for the next start to deal with.
+    match paths::pending_legacy_migration() {
+        Ok(moves) => {
+            for moved in moves {
+                checks.push(check(
+                    "migration",
+                    "warn",
+                    format!("{} will be moved to {} on the next start", moved.from.display(), moved.to.display()),
+                ));
+            }
+        }
+        Err(e) => checks.push(check("migration", "error", e.describe())),
+    }
+
+    let settings = match settings::peek_settings() {
+        Ok(s) => {
+            checks.push(check("settings", "ok", format!("{} scan root(s) configured", s.scan_roots.len())));
+            for root in &s.scan_roots {
+                if PathBuf::from(root).is_dir() {
+                    checks.push(check("scan_root", "ok", root.clone()));
+                } else {
+                    checks.push(check("scan_root", "warn", format!("Scan root does not exist: {}", root)));
+                }
+            }
+            for root in &s.home_roots {
+                if PathBuf::from(root).is_dir() {
+                    checks.push(check("home_root", "ok", root.clone()));
+                } else {
+                    checks.push(check("home_root", "warn", format!("Home root does not exist: {}", root)));
+                }
+            }
+            Some(s)
+        }
+        Err(e) => {
+            checks.push(check("settings", "error", e.describe()));
+            None
+        }
+    };
+
+    let npx = cli_bridge::find_npx();
+    if Path::new(&npx).is_absolute() {
+        checks.push(check("npx", "ok", npx));
+    } else {
+        checks.push(check(
+            "npx",
+            "warn",
+            "npx not found; installing and removing skills through the CLI will fail".to_string(),
+        ));
+    }
+
+    // Without readable settings, detection would fall back to loading (and
+    // resetting) them, so it is skipped.
+    if let Some(s) = settings {
+        match agents::detect_agents(Some(s.scan_roots), Some(s.home_roots)) {
+            Ok(found) => {
+                let detected: Vec<&str> = found.iter().filter(|a| a.detected).map(|a| a.id.as_str()).collect();
+                let status = if detected.is_empty() { "warn" } else { "ok" };
+                let message = format!("{} agent(s) detected: {}", detected.len(), detected.join(", "));
+                checks.push(check("agents", status, message));
+            }
+            Err(e) => checks.push(check("agents", "error", e.describe())),
+        }
+    }
+
+    let healthy = checks.iter().all(|c| c["status"] != "error");
+    print_json(&json!({ "healthy": healthy, "checks": checks }), args.compact);
+    Ok(healthy)
+}
+
+fn main() -> ExitCode {
+    logging::init();
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    if raw.is_empty() || raw.iter().any(|a| a == "-h" || a == "--help") {
+        // With no arguments this is a usage error, so the help goes to stderr.
+        if raw.is_empty() {
+            eprint!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let args = match Args::parse(raw) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match args.command.as_str() {
+        "list" => cmd_list(&args),
+        "agents" => cmd_agents(&args),
+        "validate" => cmd_validate(&args),
+        "lint" => cmd_lint(&args),
+        "sync" => cmd_sync(&args),
+        "test" => cmd_test(&args),
+        "trigger" => cmd_trigger(&args),
+        "doctor" => cmd_doctor(&args),
+        other => {
+            eprintln!("error: unknown command '{}'\n\n{}", other, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            let error = json!({
+                "code": e.code(),
+                "message": e.to_string(),
+                "path": e.path(),
+                "causes": e.causes(),
+            });
+            print_json(&json!({ "error": error }), args.compact);
+            ExitCode::FAILURE
+        }
+    }
+}
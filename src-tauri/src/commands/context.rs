@@ -0,0 +1,12 @@
+use crate::core::context::{self, ContextPreview};
+
+use super::error::{CommandError, CommandResult};
+
+#[tauri::command]
+pub fn preview_skill_context(
+    skill_path: String,
+    agent: String,
+    project_path: Option<String>,
+) -> CommandResult<ContextPreview> {
+    context::preview_skill_context(&skill_path, &agent, project_path.as_deref()).map_err(CommandError::from)
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+use crate::core::error::{Error, ErrorCode};
+
+/// Error returned by every Tauri command. Serialized to the frontend as
+/// `{ code, message, path?, causes?, details? }`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub causes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        CommandError {
+            code,
+            message: message.into(),
+            path: None,
+            causes: vec![],
+            details: None,
+        }
+    }
+
+    /// Wraps a non-core error, recording its source chain as causes.
+    pub fn wrap(code: ErrorCode, context: &str, err: &(dyn std::error::Error + 'static)) -> Self {
+        let mut causes = Vec::new();
+        let mut source = err.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        CommandError {
+            code,
+            message: format!("{}: {}", context, err),
+            path: None,
+            causes,
+            details: None,
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<Error> for CommandError {
+    fn from(err: Error) -> Self {
+        CommandError {
+            code: err.code(),
+            message: err.to_string(),
+            path: err.path().map(|p| p.to_string_lossy().to_string()),
+            causes: err.causes(),
+            details: err.details(),
+        }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
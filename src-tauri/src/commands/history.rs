@@ -0,0 +1,14 @@
+use crate::core::editor::FileVersion;
+use crate::core::history::{self, HistoryEntry};
+
+use super::error::{CommandError, CommandResult};
+
+#[tauri::command]
+pub fn list_file_history(path: String) -> CommandResult<Vec<HistoryEntry>> {
+    history::list_file_history(&path).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn restore_file_version(path: String, id: String) -> CommandResult<FileVersion> {
+    history::restore_file_version(&path, &id).map_err(CommandError::from)
+}
@@ -1,3 +1,4 @@
+pub mod error;
 pub mod skills;
 pub mod agents;
 pub mod editor;
@@ -6,3 +7,23 @@ pub mod sandbox;
 pub mod watcher;
 pub mod settings;
 pub mod updater;
+pub mod sync;
+pub mod trash;
+pub mod snapshot;
+pub mod skill_tests;
+pub mod context;
+pub mod trigger;
+pub mod history;
+
+/// Runs blocking core work (archiving, hashing, moving directories) on
+/// Tauri's blocking thread pool instead of the thread handling the command.
+pub(crate) async fn blocking<T, F>(work: F) -> error::CommandResult<T>
+where
+    F: FnOnce() -> crate::core::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| error::CommandError::internal(e.to_string()))?
+        .map_err(error::CommandError::from)
+}
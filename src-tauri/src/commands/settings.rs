@@ -1,48 +1,60 @@
-use serde::{Deserialize, Serialize};
-use std::fs;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Emitter;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Settings {
-    pub scan_roots: Vec<String>,
-}
+use crate::core::paths::{self, LegacyMove};
+use crate::core::settings::{self, Settings, SettingsWatcher};
+
+use super::error::{CommandError, CommandResult};
+
+static WATCHER: Mutex<Option<SettingsWatcher>> = Mutex::new(None);
 
-impl Default for Settings {
-    fn default() -> Self {
-        Settings {
-            scan_roots: vec![],
+/// Starts watching the settings file for edits made outside SkillDuck. An
+/// edit that cannot be read is emitted as `settings-error`.
+pub fn start(app: tauri::AppHandle) {
+    let on_change = move |result: crate::core::Result<Settings>| match result {
+        Ok(settings) => changed(&app, &settings),
+        Err(e) => {
+            let _ = app.emit("settings-error", CommandError::from(e));
         }
+    };
+    match settings::watch_settings(on_change) {
+        Ok(watcher) => *WATCHER.lock().unwrap_or_else(|e| e.into_inner()) = Some(watcher),
+        Err(e) => log::error!("Failed to watch the settings file: {}", e.describe()),
     }
 }
 
-fn get_settings_path() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
-    let config_dir = home.join(".skillduck");
-    fs::create_dir_all(&config_dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
-    Ok(config_dir.join("settings.json"))
+/// Emits `settings-changed` and brings the watches and the skill list in
+/// line with the new settings.
+fn changed(app: &tauri::AppHandle, settings: &Settings) {
+    let _ = app.emit("settings-changed", settings);
+    super::watcher::reconcile();
+    super::skills::reload(app);
 }
 
 #[tauri::command]
-pub fn get_settings() -> Result<Settings, String> {
-    let settings_path = get_settings_path()?;
-    
-    if settings_path.exists() {
-        let content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
-        let settings: Settings = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?;
-        Ok(settings)
-    } else {
-        Ok(Settings::default())
-    }
+pub fn get_settings() -> CommandResult<Settings> {
+    settings::current().map_err(CommandError::from)
+}
+
+/// Where an unreadable settings file was moved when the settings were reset
+/// at startup.
+#[tauri::command]
+pub fn get_settings_backup() -> Option<PathBuf> {
+    settings::reset_backup()
+}
+
+/// What was moved out of the `~/.skillduck` used by older versions.
+#[tauri::command]
+pub fn get_legacy_migration() -> CommandResult<Vec<LegacyMove>> {
+    paths::legacy_migration().map_err(CommandError::from)
 }
 
+/// Saves the settings, returning warnings about configured roots that no
+/// longer exist.
 #[tauri::command]
-pub fn save_settings(settings: Settings) -> Result<(), String> {
-    let settings_path = get_settings_path()?;
-    let content = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&settings_path, content)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
-    Ok(())
+pub fn save_settings(app: tauri::AppHandle, settings: Settings) -> CommandResult<Vec<String>> {
+    let warnings = settings::save_settings(&settings)?;
+    changed(&app, &settings::current()?);
+    Ok(warnings)
 }
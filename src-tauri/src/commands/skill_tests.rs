@@ -0,0 +1,8 @@
+use crate::core::skill_tests::{self, TestReport};
+
+use super::error::{CommandError, CommandResult};
+
+#[tauri::command]
+pub async fn run_skill_tests(path: String) -> CommandResult<TestReport> {
+    skill_tests::run_skill_tests(&path).await.map_err(CommandError::from)
+}
@@ -1,249 +1,82 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs;
-use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use tauri::{Emitter, Manager};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Skill {
-    pub name: String,
-    pub description: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub license: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub compatibility: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub metadata: Option<HashMap<String, String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub allowed_tools: Option<String>,
-    pub install_path: String,
-    pub scope: String,
-    pub agents: Vec<String>,
-    pub has_update: bool,
-    pub body: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub project_root: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-struct SkillFrontmatter {
-    name: Option<String>,
-    description: Option<String>,
-    license: Option<String>,
-    compatibility: Option<String>,
-    metadata: Option<HashMap<String, serde_yaml::Value>>,
-    #[serde(rename = "allowed-tools")]
-    allowed_tools: Option<String>,
-}
-
-fn parse_skill_md_content(content: &str) -> Option<(SkillFrontmatter, String)> {
-    let trimmed = content.trim_start();
-    if !trimmed.starts_with("---") {
-        return None;
-    }
+use crate::core::skill_set::{SkillSet, SkillsUpdate};
+use crate::core::skills::{self, ScopeFilter, Skill, SkillsDirectories};
+use crate::core::watcher::SkillChange;
 
-    let after_first = &trimmed[3..];
-    let end_idx = after_first.find("\n---")?;
-    let yaml_str = &after_first[..end_idx];
-    let body_start = end_idx + 4; // skip \n---
-    let body = if body_start < after_first.len() {
-        after_first[body_start..].trim_start_matches('\n').to_string()
-    } else {
-        String::new()
-    };
-
-    let frontmatter: SkillFrontmatter = serde_yaml::from_str(yaml_str).ok()?;
-    Some((frontmatter, body))
-}
-
-fn flatten_metadata(metadata: Option<HashMap<String, serde_yaml::Value>>) -> Option<HashMap<String, String>> {
-    metadata.map(|m| {
-        m.into_iter()
-            .map(|(k, v)| {
-                let val = match v {
-                    serde_yaml::Value::String(s) => s,
-                    serde_yaml::Value::Bool(b) => b.to_string(),
-                    serde_yaml::Value::Number(n) => n.to_string(),
-                    _ => format!("{:?}", v),
-                };
-                (k, val)
-            })
-            .collect()
-    })
-}
+use super::error::{CommandError, CommandResult};
 
-pub fn scan_directory_for_skills(base_path: &Path, scope: &str, agent_id: &str, project_root: Option<&str>) -> Vec<Skill> {
-    let mut skills = Vec::new();
+/// The skill set, managed by Tauri: loaded on the first `list_skills`, then
+/// kept current from watcher events.
+#[derive(Default)]
+pub struct SkillsState(Mutex<Option<SkillSet>>);
 
-    if !base_path.exists() {
-        return skills;
+impl SkillsState {
+    fn lock(&self) -> MutexGuard<'_, Option<SkillSet>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
     }
+}
 
-    for entry in WalkDir::new(base_path).follow_links(true).max_depth(4).into_iter().filter_map(|e| e.ok()) {
-        if entry.file_name() == "SKILL.md" {
-            if let Ok(content) = fs::read_to_string(entry.path()) {
-                if let Some((fm, body)) = parse_skill_md_content(&content) {
-                    let name = fm.name.unwrap_or_else(|| {
-                        entry
-                            .path()
-                            .parent()
-                            .and_then(|p| p.file_name())
-                            .map(|n| n.to_string_lossy().to_string())
-                            .unwrap_or_default()
-                    });
-
-                    let description = fm.description.unwrap_or_default();
-
-                    let skill = Skill {
-                        name,
-                        description,
-                        license: fm.license,
-                        compatibility: fm.compatibility,
-                        metadata: flatten_metadata(fm.metadata),
-                        allowed_tools: fm.allowed_tools,
-                        install_path: entry.path().to_string_lossy().to_string(),
-                        scope: scope.to_string(),
-                        agents: vec![agent_id.to_string()],
-                        has_update: false,
-                        body,
-                        project_root: project_root.map(|s| s.to_string()),
-                    };
-
-                    skills.push(skill);
-                }
-            }
+/// Lists installed skills from memory. `refresh` rescans every skills
+/// directory first, for changes the watcher could not see.
+#[tauri::command]
+pub fn list_skills(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SkillsState>,
+    scope: String,
+    refresh: Option<bool>,
+) -> CommandResult<Vec<Skill>> {
+    let scope = ScopeFilter::parse(&scope).map_err(CommandError::from)?;
+    let mut guard = state.lock();
+    match guard.as_mut() {
+        Some(set) if refresh.unwrap_or(false) => {
+            let current = SkillSet::current()?;
+            emit_update(&app, set.replace(current));
         }
+        Some(_) => {}
+        None => *guard = Some(SkillSet::current()?),
     }
-
-    skills
+    Ok(guard.as_ref().map(|set| set.skills(scope)).unwrap_or_default())
 }
 
-pub fn merge_skills(all_skills: Vec<Skill>) -> Vec<Skill> {
-    let mut map: HashMap<String, Skill> = HashMap::new();
-
-    for skill in all_skills {
-        let key = format!("{}::{}", skill.scope, skill.name);
-        if let Some(existing) = map.get_mut(&key) {
-            for agent in &skill.agents {
-                if !existing.agents.contains(agent) {
-                    existing.agents.push(agent.clone());
-                }
-            }
-        } else {
-            map.insert(key, skill);
-        }
+fn emit_update(app: &tauri::AppHandle, update: SkillsUpdate) {
+    if !update.is_empty() {
+        let _ = app.emit("skills-updated", update);
     }
-
-    let mut result: Vec<Skill> = map.into_values().collect();
-    result.sort_by(|a, b| a.name.cmp(&b.name));
-    result
 }
 
-#[tauri::command]
-pub fn list_skills(scope: String) -> Result<Vec<Skill>, String> {
-    let agents = super::agents::get_agent_definitions();
-    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
-    let settings = super::settings::get_settings().unwrap_or_default();
-    let scan_roots: Vec<PathBuf> = settings.scan_roots.iter().map(|r| PathBuf::from(r)).collect();
-
-    let mut all_skills = Vec::new();
-
-    for agent in &agents {
-        if scope == "all" || scope == "global" {
-            let global_path = home.join(&agent.global_path);
-            let skills = scan_directory_for_skills(&global_path, "global", &agent.id, None);
-            all_skills.extend(skills);
-        }
+/// Applies a batch of watcher changes to the skill set, emitting
+/// `skills-updated` with the skills that changed.
+pub fn apply_changes(app: &tauri::AppHandle, changes: &[SkillChange]) {
+    let state = app.state::<SkillsState>();
+    let mut guard = state.lock();
+    // Not loaded yet: the first `list_skills` will see these changes anyway.
+    if let Some(set) = guard.as_mut() {
+        emit_update(app, set.apply(changes));
+    }
+}
 
-        if scope == "all" || scope == "project" {
-            for root in &scan_roots {
-                if !root.exists() {
-                    continue;
-                }
-                for entry in WalkDir::new(root)
-                    .follow_links(true)
-                    .into_iter()
-                    .filter_entry(|e| {
-                        if let Some(name) = e.file_name().to_str() {
-                            !matches!(
-                                name,
-                                "node_modules" | ".git" | "target" | "dist" | "build" |
-                                ".next" | ".nuxt" | ".venv" | "venv" | "__pycache__" |
-                                ".cache" | "vendor" | "bower_components"
-                            )
-                        } else {
-                            true
-                        }
-                    })
-                    .filter_map(|e| e.ok())
-                {
-                    let path = entry.path();
-                    if path.is_dir() && path.to_string_lossy().ends_with(&agent.project_path) {
-                        // Derive project root by stripping the agent's project_path segments
-                        let depth = agent.project_path.split('/').filter(|s| !s.is_empty()).count();
-                        let mut project_root_opt = Some(path.to_path_buf());
-                        for _ in 0..depth {
-                            project_root_opt = project_root_opt.and_then(|p| p.parent().map(|pp| pp.to_path_buf()));
-                        }
-                        let project_root_str = project_root_opt.map(|p| p.to_string_lossy().to_string());
-                        let skills = scan_directory_for_skills(path, "project", &agent.id, project_root_str.as_deref());
-                        all_skills.extend(skills);
-                    }
-                }
-            }
+/// Rescans the skill set; called when the scan roots may have changed.
+pub fn reload(app: &tauri::AppHandle) {
+    let state = app.state::<SkillsState>();
+    let mut guard = state.lock();
+    if let Some(set) = guard.as_mut() {
+        match SkillSet::current() {
+            Ok(current) => emit_update(app, set.replace(current)),
+            Err(e) => log::error!("Failed to rescan skills: {}", e.describe()),
         }
     }
-
-    Ok(merge_skills(all_skills))
 }
 
 #[tauri::command]
-pub fn parse_skill_md(path: String) -> Result<Skill, String> {
-    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
-    let (fm, body) = parse_skill_md_content(&content)
-        .ok_or_else(|| "Invalid SKILL.md format: missing frontmatter".to_string())?;
-
-    let name = fm.name.unwrap_or_default();
-    let description = fm.description.unwrap_or_default();
-
-    Ok(Skill {
-        name,
-        description,
-        license: fm.license,
-        compatibility: fm.compatibility,
-        metadata: flatten_metadata(fm.metadata),
-        allowed_tools: fm.allowed_tools,
-        install_path: path,
-        scope: "unknown".to_string(),
-        agents: vec![],
-        has_update: false,
-        body,
-        project_root: None,
-    })
+pub fn parse_skill_md(path: String) -> CommandResult<Skill> {
+    skills::parse_skill_md(&path).map_err(CommandError::from)
 }
 
 #[tauri::command]
-pub fn get_skills_directories() -> Result<serde_json::Value, String> {
-    let agents = super::agents::get_agent_definitions();
-    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+pub fn get_skills_directories() -> CommandResult<SkillsDirectories> {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-    let mut project_paths = Vec::new();
-    let mut global_paths = Vec::new();
-
-    for agent in &agents {
-        let global = home.join(&agent.global_path);
-        if global.exists() {
-            global_paths.push(global.to_string_lossy().to_string());
-        }
-        let project = cwd.join(&agent.project_path);
-        if project.exists() {
-            project_paths.push(project.to_string_lossy().to_string());
-        }
-    }
-
-    Ok(serde_json::json!({
-        "project": project_paths,
-        "global": global_paths,
-    }))
+    skills::get_skills_directories(&cwd).map_err(CommandError::from)
 }
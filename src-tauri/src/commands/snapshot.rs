@@ -0,0 +1,29 @@
+use crate::core::snapshot::{self, SnapshotInfo, SnapshotStatus};
+
+use super::blocking;
+use super::error::CommandResult;
+
+#[tauri::command]
+pub async fn create_snapshot(
+    agent: String,
+    global: bool,
+    project_path: Option<String>,
+    label: Option<String>,
+) -> CommandResult<SnapshotInfo> {
+    blocking(move || snapshot::create_snapshot(&agent, global, project_path.as_deref(), label)).await
+}
+
+#[tauri::command]
+pub async fn list_snapshots(agent: Option<String>) -> CommandResult<Vec<SnapshotStatus>> {
+    blocking(move || snapshot::list_snapshots(agent.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn restore_snapshot(id: String) -> CommandResult<SnapshotInfo> {
+    blocking(move || snapshot::restore_snapshot(&id)).await
+}
+
+#[tauri::command]
+pub async fn delete_snapshot(id: String) -> CommandResult<()> {
+    blocking(move || snapshot::delete_snapshot(&id)).await
+}
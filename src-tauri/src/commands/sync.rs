@@ -0,0 +1,15 @@
+use crate::core::sync::{self, SyncReport};
+
+use super::blocking;
+use super::error::CommandResult;
+
+#[tauri::command]
+pub async fn sync_skills(
+    from_agent: String,
+    to_agents: Vec<String>,
+    global: bool,
+    project_path: Option<String>,
+    dry_run: bool,
+) -> CommandResult<SyncReport> {
+    blocking(move || sync::sync_skills(&from_agent, &to_agents, global, project_path.as_deref(), dry_run)).await
+}
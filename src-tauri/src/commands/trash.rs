@@ -0,0 +1,29 @@
+use crate::core::trash::{self, TrashEntry};
+
+use super::blocking;
+use super::error::CommandResult;
+
+#[tauri::command]
+pub async fn remove_skill(
+    skill_name: String,
+    agents: Vec<String>,
+    global: bool,
+    project_path: Option<String>,
+) -> CommandResult<TrashEntry> {
+    blocking(move || trash::remove_skill(&skill_name, &agents, global, project_path.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn list_trash() -> CommandResult<Vec<TrashEntry>> {
+    blocking(trash::list_trash).await
+}
+
+#[tauri::command]
+pub async fn restore_skill(id: String) -> CommandResult<TrashEntry> {
+    blocking(move || trash::restore_skill(&id)).await
+}
+
+#[tauri::command]
+pub async fn empty_trash() -> CommandResult<usize> {
+    blocking(trash::empty_trash).await
+}
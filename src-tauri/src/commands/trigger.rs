@@ -0,0 +1,8 @@
+use crate::core::trigger::{self, TriggerReport};
+
+use super::error::{CommandError, CommandResult};
+
+#[tauri::command]
+pub fn simulate_trigger(prompt: String, agent: String, project_path: Option<String>) -> CommandResult<TriggerReport> {
+    trigger::simulate_trigger(&prompt, &agent, project_path.as_deref()).map_err(CommandError::from)
+}
@@ -2,6 +2,10 @@ use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use tauri_plugin_updater::UpdaterExt;
 
+use crate::core::error::ErrorCode;
+
+use super::error::{CommandError, CommandResult};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppUpdateInfo {
     pub version: String,
@@ -9,15 +13,19 @@ pub struct AppUpdateInfo {
     pub date: Option<String>,
 }
 
+fn updater_error(context: &str, err: tauri_plugin_updater::Error) -> CommandError {
+    CommandError::wrap(ErrorCode::UpdaterFailed, context, &err)
+}
+
 #[tauri::command]
-pub async fn check_for_app_update(app: AppHandle) -> Result<Option<AppUpdateInfo>, String> {
+pub async fn check_for_app_update(app: AppHandle) -> CommandResult<Option<AppUpdateInfo>> {
     let updater = app
         .updater()
-        .map_err(|e| format!("Updater init error: {e}"))?;
+        .map_err(|e| updater_error("Updater init error", e))?;
     let update = updater
         .check()
         .await
-        .map_err(|e| format!("Update check failed: {e}"))?;
+        .map_err(|e| updater_error("Update check failed", e))?;
     Ok(update.map(|u| AppUpdateInfo {
         version: u.version.to_string(),
         body: u.body.clone(),
@@ -26,19 +34,19 @@ pub async fn check_for_app_update(app: AppHandle) -> Result<Option<AppUpdateInfo
 }
 
 #[tauri::command]
-pub async fn install_app_update(app: AppHandle) -> Result<(), String> {
+pub async fn install_app_update(app: AppHandle) -> CommandResult<()> {
     let updater = app
         .updater()
-        .map_err(|e| format!("Updater init error: {e}"))?;
+        .map_err(|e| updater_error("Updater init error", e))?;
     let update = updater
         .check()
         .await
-        .map_err(|e| format!("Update check failed: {e}"))?;
+        .map_err(|e| updater_error("Update check failed", e))?;
     if let Some(update) = update {
         update
             .download_and_install(|_chunk, _total| {}, || {})
             .await
-            .map_err(|e| format!("Install failed: {e}"))?;
+            .map_err(|e| updater_error("Install failed", e))?;
         app.restart();
     }
     Ok(())
@@ -1,47 +1,78 @@
-use notify::{recommended_watcher, Event, RecursiveMode, Watcher};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread;
 use tauri::Emitter;
 
-type WatcherMap = Arc<Mutex<HashMap<String, notify::RecommendedWatcher>>>;
+use crate::core::watcher::{WatchInfo, WatchService};
 
-lazy_static::lazy_static! {
-    static ref WATCHERS: WatcherMap = Arc::new(Mutex::new(HashMap::new()));
+use super::blocking;
+use super::error::{CommandError, CommandResult};
+
+static SERVICE: OnceLock<WatchService> = OnceLock::new();
+
+fn service() -> CommandResult<&'static WatchService> {
+    SERVICE
+        .get()
+        .ok_or_else(|| CommandError::internal("The watch service has not started".to_string()))
 }
 
-#[tauri::command]
-pub fn watch_skills_dir(app: tauri::AppHandle, path: String) -> Result<(), String> {
-    let watch_path = PathBuf::from(&path);
-    if !watch_path.exists() {
-        return Err(format!("Path does not exist: {}", path));
+/// Starts watching every agent's global skills directory and every scan root,
+/// emitting `skill-changed` for each change and `skills-updated` for the
+/// skills that changed.
+pub fn start(app: tauri::AppHandle) {
+    let service = SERVICE.get_or_init(|| {
+        WatchService::new(move |changes| {
+            for change in &changes {
+                let _ = app.emit("skill-changed", change);
+            }
+            super::skills::apply_changes(&app, &changes);
+            super::editor::notify_open_files(&app, &changes);
+        })
+    });
+    // Indexing the skills below every root takes a while; keep it off startup.
+    thread::spawn(move || service.reconcile());
+}
+
+/// Re-reads agents and settings; called when the settings change.
+pub fn reconcile() {
+    if let Some(service) = SERVICE.get() {
+        service.reconcile();
     }
+}
 
-    let app_handle = app.clone();
-    let mut watcher = recommended_watcher(move |res: Result<Event, notify::Error>| {
-        if let Ok(event) = res {
-            for path in &event.paths {
-                if path.file_name().map(|n| n == "SKILL.md").unwrap_or(false) {
-                    let _ = app_handle.emit("skill-changed", path.to_string_lossy().to_string());
-                }
-            }
-        }
-    })
-    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+/// Takes a watch on `dir` for `owner`, to be released with [`unwatch`].
+/// Returns the directory if the watch was taken.
+pub fn watch(dir: &Path, owner: &str) -> Option<PathBuf> {
+    SERVICE.get()?.watch(&dir.to_string_lossy(), owner).ok()
+}
+
+pub fn unwatch(dir: &Path, owner: &str) {
+    if let Some(service) = SERVICE.get() {
+        service.unwatch(&dir.to_string_lossy(), owner);
+    }
+}
 
-    watcher
-        .watch(&watch_path, RecursiveMode::Recursive)
-        .map_err(|e| format!("Failed to watch {}: {}", path, e))?;
+/// Owner of the watches taken from the frontend.
+const APP_OWNER: &str = "app";
 
-    let mut watchers = WATCHERS.lock().map_err(|e| e.to_string())?;
-    watchers.insert(path, watcher);
+#[tauri::command]
+pub async fn watch_skills_dir(path: String) -> CommandResult<()> {
+    let service = service()?;
+    blocking(move || service.watch(&path, APP_OWNER).map(|_| ())).await
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn unwatch_skills_dir(path: String) -> CommandResult<()> {
+    let service = service()?;
+    blocking(move || {
+        service.unwatch(&path, APP_OWNER);
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn unwatch_skills_dir(path: String) -> Result<(), String> {
-    let mut watchers = WATCHERS.lock().map_err(|e| e.to_string())?;
-    watchers.remove(&path);
-    Ok(())
+pub async fn list_watches() -> CommandResult<Vec<WatchInfo>> {
+    let service = service()?;
+    blocking(move || Ok(service.list())).await
 }
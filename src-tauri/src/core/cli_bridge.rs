@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command as AsyncCommand;
+
+use super::{Error, Result};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CliOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+}
+
+/// Locates `npx`, falling back to the bare name when nothing is found.
+pub fn find_npx() -> String {
+    find_program("npx").unwrap_or_else(|| "npx".to_string())
+}
+
+/// Locates a program the user has installed, looking in the usual places
+/// and version managers that a packaged app's `PATH` tends to miss.
+pub fn find_program(name: &str) -> Option<String> {
+    let home = std::env::var("HOME").unwrap_or_default();
+
+    // 1. Check common fixed paths
+    for dir in &["/opt/homebrew/bin", "/usr/local/bin"] {
+        let candidate = std::path::Path::new(dir).join(name);
+        if candidate.exists() {
+            return Some(candidate.to_string_lossy().to_string());
+        }
+    }
+
+    // 2. Scan ~/.nvm/versions/node/*/bin and ~/.fnm/node-versions/*/installation/bin
+    //    — pick the latest version
+    if !home.is_empty() {
+        let managers = [(".nvm/versions/node", "bin"), (".fnm/node-versions", "installation/bin")];
+        for (versions_dir, bin) in managers {
+            let Ok(entries) = std::fs::read_dir(std::path::Path::new(&home).join(versions_dir)) else {
+                continue;
+            };
+            let mut versions: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+            // Sort descending so we try the newest version first
+            versions.sort_by_key(|e| std::cmp::Reverse(e.file_name()));
+            for entry in versions {
+                let candidate = entry.path().join(bin).join(name);
+                if candidate.exists() {
+                    return Some(candidate.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+
+    // 3. Per-user install locations (deno's installer, pip/pipx)
+    if !home.is_empty() {
+        for dir in &[".deno/bin", ".local/bin"] {
+            let candidate = std::path::Path::new(&home).join(dir).join(name);
+            if candidate.exists() {
+                return Some(candidate.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    // 4. Try `which` with extended PATH
+    let mut extended_path = std::env::var("PATH").unwrap_or_default();
+    for extra in &["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin", "/bin"] {
+        if !extended_path.split(':').any(|p| p == *extra) {
+            extended_path = format!("{}:{}", extended_path, extra);
+        }
+    }
+    if let Ok(output) = std::process::Command::new("which")
+        .arg(name)
+        .env("PATH", &extended_path)
+        .output()
+    {
+        if output.status.success() {
+            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !path.is_empty() {
+                return Some(path);
+            }
+        }
+    }
+
+    // 5. Try login shells (zsh first for macOS, then sh)
+    let script = format!("which {0} 2>/dev/null || command -v {0} 2>/dev/null", name);
+    let suffix = format!("/{}", name);
+    for shell in &["zsh", "bash", "sh"] {
+        if let Ok(output) = std::process::Command::new(shell)
+            .args(["-lc", &script])
+            .output()
+        {
+            if output.status.success() {
+                let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                // Take only the first line in case of shell startup messages
+                if let Some(first_line) = path.lines().find(|l| l.ends_with(&suffix)) {
+                    return Some(first_line.to_string());
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Builds a rich PATH so node can be found by npx even in packaged app environments.
+/// Includes the directory of the resolved npx binary so node is available alongside it.
+fn extended_path(npx: &str) -> String {
+    let mut path_val = std::env::var("PATH").unwrap_or_default();
+    let npx_dir = std::path::Path::new(npx).parent().map(|p| p.to_string_lossy().to_string());
+    let extras: &[&str] = &["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin", "/bin"];
+    for extra in extras {
+        if !path_val.split(':').any(|p| p == *extra) {
+            path_val = format!("{}:{}", path_val, extra);
+        }
+    }
+    if let Some(dir) = npx_dir.filter(|d| !d.is_empty()) {
+        if !path_val.split(':').any(|p| p == dir) {
+            path_val = format!("{}:{}", dir, path_val);
+        }
+    }
+    path_val
+}
+
+fn spawn_error(npx: String, err: std::io::Error) -> Error {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        Error::NpxNotFound
+    } else {
+        Error::Spawn { program: npx, source: err }
+    }
+}
+
+fn skills_command(npx: &str, args: &[String], cwd: Option<&str>) -> AsyncCommand {
+    let mut cmd = AsyncCommand::new(npx);
+    cmd.arg("skills").args(args);
+
+    if let Some(dir) = cwd.filter(|d| !d.is_empty()) {
+        cmd.current_dir(dir);
+    }
+
+    cmd.env("PATH", extended_path(npx));
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    cmd
+}
+
+/// Runs `npx skills <args> -y` and collects its output.
+pub async fn run_skills_command(mut args: Vec<String>, cwd: Option<&str>) -> Result<CliOutput> {
+    let npx = find_npx();
+    args.push("-y".to_string()); // non-interactive
+
+    let output = skills_command(&npx, &args, cwd)
+        .output()
+        .await
+        .map_err(|e| spawn_error(npx, e))?;
+
+    Ok(CliOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_code: output.status.code().unwrap_or(-1),
+    })
+}
+
+/// Runs `npx skills <args>`, passing each stdout line to `on_line` as it arrives.
+/// Returns the exit code.
+pub async fn stream_skills_command<F>(args: Vec<String>, cwd: Option<&str>, mut on_line: F) -> Result<i32>
+where
+    F: FnMut(&str),
+{
+    let npx = find_npx();
+
+    let mut child = skills_command(&npx, &args, cwd)
+        .spawn()
+        .map_err(|e| spawn_error(npx.clone(), e))?;
+
+    if let Some(stdout) = child.stdout.take() {
+        let reader = BufReader::new(stdout);
+        let mut lines = reader.lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            on_line(&line);
+        }
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| Error::Spawn { program: npx, source: e })?;
+    Ok(status.code().unwrap_or(-1))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddSkillOptions {
+    pub source: String,
+    pub agents: Vec<String>,
+    pub skills: Vec<String>,
+    pub global: bool,
+    pub list_only: bool,
+    pub all: bool,
+    pub project_path: Option<String>,
+}
+
+pub async fn add_skill(opts: AddSkillOptions) -> Result<CliOutput> {
+    let mut args = vec!["add".to_string(), opts.source];
+
+    for agent in &opts.agents {
+        args.push("-a".to_string());
+        args.push(agent.clone());
+    }
+    for skill in &opts.skills {
+        args.push("-s".to_string());
+        args.push(skill.clone());
+    }
+    if opts.global {
+        args.push("-g".to_string());
+    }
+    if opts.list_only {
+        args.push("--list".to_string());
+    }
+    if opts.all {
+        args.push("--all".to_string());
+    }
+
+    // For project scope, run CLI in the specified project directory
+    let cwd = if !opts.global { opts.project_path.as_deref() } else { None };
+    run_skills_command(args, cwd).await
+}
+
+pub async fn remove_skill(
+    skill_names: Vec<String>,
+    agents: &[String],
+    global: bool,
+    project_path: Option<&str>,
+) -> Result<CliOutput> {
+    let mut args = vec!["remove".to_string()];
+    args.extend(skill_names);
+
+    for agent in agents {
+        args.push("-a".to_string());
+        args.push(agent.clone());
+    }
+    if global {
+        args.push("-g".to_string());
+    }
+
+    let cwd = if !global { project_path } else { None };
+    run_skills_command(args, cwd).await
+}
+
+pub async fn check_updates() -> Result<CliOutput> {
+    run_skills_command(vec!["check".to_string()], None).await
+}
+
+pub async fn update_skills(skill_name: Option<String>) -> Result<CliOutput> {
+    let mut args = vec!["update".to_string()];
+    args.extend(skill_name);
+    run_skills_command(args, None).await
+}
+
+/// Runs `npx skills init <name>` inside `path`. Unlike the other commands this
+/// one is interactive-free already and takes no `-y`.
+pub async fn init_skill(name: &str, path: &str) -> Result<CliOutput> {
+    let npx = find_npx();
+    let args = vec!["init".to_string(), name.to_string()];
+
+    let output = skills_command(&npx, &args, Some(path))
+        .output()
+        .await
+        .map_err(|e| spawn_error(npx, e))?;
+
+    Ok(CliOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        exit_code: output.status.code().unwrap_or(-1),
+    })
+}
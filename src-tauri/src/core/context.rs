@@ -0,0 +1,218 @@
+//! What an agent loads from its skills, stage by stage.
+//!
+//! Agents disclose skills progressively: the name and description of every
+//! installed skill are in context from the start (stage 1), the SKILL.md body
+//! is read when a skill is activated (stage 2), and files the body points to
+//! are read on demand (stage 3). Stage 1 is rendered in the
+//! `<available_skills>` format from the Agent Skills integration guide.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use super::skills::{agent_skills, parse_skill_md_content, Skill};
+use super::{Error, Result};
+
+/// Resources larger than this are counted but not included in the preview.
+const MAX_RESOURCE_BYTES: u64 = 256 * 1024;
+
+lazy_static! {
+    /// Targets of Markdown links and images: `[text](target)`.
+    static ref LINK_RE: Regex = Regex::new(r"\]\(\s*<?([^)\s>]+)>?(?:\s+[^)]*)?\)").unwrap();
+    /// Inline code spans that look like a relative path: `scripts/run.py`.
+    static ref CODE_PATH_RE: Regex = Regex::new(r"`(\.?/?[\w.-]+(?:/[\w.-]+)*\.\w+)`").unwrap();
+}
+
+/// The usual rough estimate of four characters per token.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourcePreview {
+    /// Path relative to the skill directory, as referenced in the body.
+    pub path: String,
+    pub exists: bool,
+    pub size: u64,
+    pub tokens: usize,
+    /// Text content; absent for missing, binary or large files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub binary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentContextTotals {
+    /// Installed skills visible to the agent.
+    pub skills: usize,
+    /// Stage 1 for every installed skill, as loaded at startup.
+    pub available_skills: String,
+    pub metadata_tokens: usize,
+    /// Tokens if every skill were activated.
+    pub body_tokens: usize,
+    /// Tokens if every referenced resource were read.
+    pub resource_tokens: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextPreview {
+    pub skill: String,
+    pub agent: String,
+    /// Stage 1: the skill's entry in the available skills list.
+    pub metadata: String,
+    pub metadata_tokens: usize,
+    /// Stage 2: the SKILL.md body.
+    pub body: String,
+    pub body_tokens: usize,
+    /// Stage 3: files referenced from the body.
+    pub resources: Vec<ResourcePreview>,
+    pub resource_tokens: usize,
+    pub totals: AgentContextTotals,
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn render_metadata(name: &str, description: &str, location: &str) -> String {
+    format!(
+        "<skill>\n<name>{}</name>\n<description>{}</description>\n<location>{}</location>\n</skill>",
+        xml_escape(name),
+        xml_escape(description),
+        xml_escape(location)
+    )
+}
+
+/// Relative paths referenced from a SKILL.md body: link targets first, then
+/// paths in inline code.
+fn referenced_paths(body: &str) -> Vec<String> {
+    let links = LINK_RE.captures_iter(body).map(|c| c[1].to_string());
+    let code = CODE_PATH_RE.captures_iter(body).map(|c| c[1].to_string());
+
+    let mut paths: Vec<String> = Vec::new();
+    for target in links.chain(code) {
+        if target.contains("://") || target.starts_with('#') || target.starts_with("mailto:") {
+            continue;
+        }
+        let target = target.split('#').next().unwrap_or_default();
+        let target = target.strip_prefix("./").unwrap_or(target).to_string();
+        let inside = Path::new(&target).components().all(|c| matches!(c, Component::Normal(_)));
+        if !target.is_empty() && inside && target != "SKILL.md" && !paths.contains(&target) {
+            paths.push(target);
+        }
+    }
+    paths
+}
+
+fn preview_resource(skill_dir: &Path, path: String) -> ResourcePreview {
+    let full = skill_dir.join(&path);
+    let Some(metadata) = fs::metadata(&full).ok().filter(|m| m.is_file()) else {
+        return ResourcePreview {
+            path,
+            exists: false,
+            size: 0,
+            tokens: 0,
+            content: None,
+            binary: false,
+        };
+    };
+
+    // Large files are judged by their first part and counted by their size,
+    // so a huge resource is never read into memory.
+    let size = metadata.len();
+    let mut bytes = Vec::new();
+    let read = fs::File::open(&full).and_then(|file| file.take(MAX_RESOURCE_BYTES).read_to_end(&mut bytes));
+    let truncated = size > MAX_RESOURCE_BYTES;
+    let is_text = read.is_ok()
+        && !bytes.contains(&0)
+        && match std::str::from_utf8(&bytes) {
+            Ok(_) => true,
+            // A character cut in half at the read limit does not make the file binary.
+            Err(e) => truncated && e.error_len().is_none(),
+        };
+    let content = (is_text && !truncated).then(|| String::from_utf8_lossy(&bytes).into_owned());
+    ResourcePreview {
+        path,
+        exists: true,
+        size,
+        tokens: match &content {
+            Some(text) => estimate_tokens(text),
+            None if is_text => (size as usize).div_ceil(4),
+            None => 0,
+        },
+        binary: !is_text,
+        content,
+    }
+}
+
+/// Stage 3 for the skill whose SKILL.md is at `skill_md`.
+fn resources_of(skill_md: &Path, body: &str) -> Vec<ResourcePreview> {
+    let skill_dir = skill_md.parent().unwrap_or(Path::new("."));
+    referenced_paths(body)
+        .into_iter()
+        .map(|path| preview_resource(skill_dir, path))
+        .collect()
+}
+
+fn agent_totals(skills: &[Skill]) -> AgentContextTotals {
+    let mut available = String::from("<available_skills>\n");
+    let mut body_tokens = 0;
+    let mut resource_tokens = 0;
+    for skill in skills {
+        available.push_str(&render_metadata(&skill.name, &skill.description, &skill.install_path));
+        available.push('\n');
+        body_tokens += estimate_tokens(&skill.body);
+        resource_tokens += resources_of(Path::new(&skill.install_path), &skill.body)
+            .iter()
+            .map(|r| r.tokens)
+            .sum::<usize>();
+    }
+    available.push_str("</available_skills>");
+
+    AgentContextTotals {
+        skills: skills.len(),
+        metadata_tokens: estimate_tokens(&available),
+        available_skills: available,
+        body_tokens,
+        resource_tokens,
+    }
+}
+
+/// Previews what `agent_id` would load for the skill at `skill_path` (a
+/// SKILL.md or its directory), with totals over every skill installed for the
+/// agent globally and, if given, in `project_path`.
+pub fn preview_skill_context(skill_path: &str, agent_id: &str, project_path: Option<&str>) -> Result<ContextPreview> {
+    let path = Path::new(skill_path);
+    let skill_md: PathBuf = if path.is_dir() { path.join("SKILL.md") } else { path.to_path_buf() };
+    let content = fs::read_to_string(&skill_md).map_err(|e| Error::io("read", &skill_md, e))?;
+    let (frontmatter, body) = parse_skill_md_content(&content)?;
+
+    let name = frontmatter.name.unwrap_or_else(|| {
+        skill_md
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let metadata = render_metadata(
+        &name,
+        frontmatter.description.as_deref().unwrap_or_default(),
+        &skill_md.to_string_lossy(),
+    );
+    let resources = resources_of(&skill_md, &body);
+
+    Ok(ContextPreview {
+        skill: name,
+        agent: agent_id.to_string(),
+        metadata_tokens: estimate_tokens(&metadata),
+        metadata,
+        body_tokens: estimate_tokens(&body),
+        body,
+        resource_tokens: resources.iter().map(|r| r.tokens).sum(),
+        resources,
+        totals: agent_totals(&agent_skills(agent_id, project_path)?),
+    })
+}
@@ -0,0 +1,531 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use super::context::estimate_tokens;
+use super::error::ErrorCode;
+use super::fs::write_atomic;
+use super::history;
+use super::project_config::ProjectConfig;
+use super::settings;
+use super::skills::split_frontmatter;
+use super::{Error, Result};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationResult {
+    pub valid: bool,
+    pub errors: Vec<DiagnosticItem>,
+    pub warnings: Vec<DiagnosticItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiagnosticItem {
+    pub field: String,
+    pub message: String,
+    pub severity: String,
+}
+
+/// A SKILL.md split into flattened frontmatter fields and body, as shown in the editor.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillDocument {
+    pub frontmatter: HashMap<String, String>,
+    pub body: String,
+    /// The version of the file the document was read from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<FileVersion>,
+}
+
+/// The content of a file at some point: its modification time and a hash of
+/// its bytes. Versions with the same hash have the same content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileVersion {
+    /// Milliseconds since the Unix epoch.
+    pub mtime: u64,
+    pub hash: String,
+}
+
+impl FileVersion {
+    fn of(content: &[u8], metadata: &fs::Metadata) -> FileVersion {
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        FileVersion {
+            mtime,
+            hash: format!("{:x}", Sha256::digest(content)),
+        }
+    }
+
+    pub fn same_content(&self, other: &FileVersion) -> bool {
+        self.hash == other.hash
+    }
+}
+
+/// Reads a text file together with its version.
+pub fn read_versioned(path: &str) -> Result<(String, FileVersion)> {
+    let metadata = fs::metadata(path).map_err(|e| Error::io("read", path, e))?;
+    let content = fs::read_to_string(path).map_err(|e| Error::io("read", path, e))?;
+    let version = FileVersion::of(content.as_bytes(), &metadata);
+    Ok((content, version))
+}
+
+/// The version of the file at `path`, or `None` if there is no file.
+pub fn file_version(path: &str) -> Result<Option<FileVersion>> {
+    match read_versioned(path) {
+        Ok((_, version)) => Ok(Some(version)),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// A save refused because the file changed since the editor read it. The
+/// two diffs start from the version the editor read, making a three-way
+/// comparison between it, the editor and the disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditConflict {
+    pub expected: FileVersion,
+    /// The version on disk; absent if the file was deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<FileVersion>,
+    /// Changes made in the editor.
+    pub local_diff: String,
+    /// Changes made on disk.
+    pub disk_diff: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_content: Option<String>,
+}
+
+/// A file open in the editor changed on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFileChange {
+    /// The path the file was opened with.
+    pub path: String,
+    /// The version now on disk; absent if the file was deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<FileVersion>,
+}
+
+fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(old_name, new_name)
+        .to_string()
+}
+
+pub fn read_skill_md(path: &str) -> Result<SkillDocument> {
+    let (content, version) = read_versioned(path)?;
+    let mut document = parse_skill_document(&content)?;
+    document.version = Some(version);
+    Ok(document)
+}
+
+pub fn parse_skill_document(content: &str) -> Result<SkillDocument> {
+    let (yaml_str, body) = split_frontmatter(content)?;
+    let frontmatter: HashMap<String, serde_yaml::Value> = serde_yaml::from_str(yaml_str)?;
+
+    let mut flat: HashMap<String, String> = HashMap::new();
+    for (key, value) in frontmatter {
+        match value {
+            serde_yaml::Value::String(s) => {
+                flat.insert(key, s);
+            }
+            serde_yaml::Value::Mapping(m) => {
+                for (mk, mv) in m {
+                    if let serde_yaml::Value::String(k) = mk {
+                        let val = match mv {
+                            serde_yaml::Value::String(s) => s,
+                            other => format!("{:?}", other),
+                        };
+                        flat.insert(format!("{}.{}", key, k), val);
+                    }
+                }
+            }
+            other => {
+                flat.insert(key, format!("{:?}", other));
+            }
+        }
+    }
+
+    Ok(SkillDocument {
+        frontmatter: flat,
+        body: body.to_string(),
+        version: None,
+    })
+}
+
+/// Renders editor fields back into SKILL.md text, required fields first.
+pub fn render_skill_md(frontmatter: &HashMap<String, String>, body: &str) -> String {
+    let mut yaml_lines = Vec::new();
+
+    // Required fields first
+    if let Some(name) = frontmatter.get("name") {
+        yaml_lines.push(format!("name: {}", name));
+    }
+    if let Some(desc) = frontmatter.get("description") {
+        yaml_lines.push(format!("description: {}", desc));
+    }
+
+    // Optional fields
+    if let Some(license) = frontmatter.get("license") {
+        if !license.is_empty() {
+            yaml_lines.push(format!("license: {}", license));
+        }
+    }
+    if let Some(compat) = frontmatter.get("compatibility") {
+        if !compat.is_empty() {
+            yaml_lines.push(format!("compatibility: {}", compat));
+        }
+    }
+    if let Some(tools) = frontmatter.get("allowed_tools") {
+        if !tools.is_empty() {
+            yaml_lines.push(format!("allowed-tools: {}", tools));
+        }
+    }
+
+    // Metadata
+    let metadata_keys: Vec<_> = frontmatter
+        .keys()
+        .filter(|k| k.starts_with("metadata."))
+        .collect();
+    if !metadata_keys.is_empty() {
+        yaml_lines.push("metadata:".to_string());
+        for key in metadata_keys {
+            let field = key.strip_prefix("metadata.").unwrap();
+            let value = &frontmatter[key];
+            if !value.is_empty() {
+                yaml_lines.push(format!("  {}: \"{}\"", field, value));
+            }
+        }
+    }
+
+    format!("---\n{}\n---\n\n{}", yaml_lines.join("\n"), body)
+}
+
+/// The lock for writes to `path` through SkillDuck, so that the version
+/// check and the write of one save never interleave with another write.
+pub(crate) fn save_lock(path: &Path) -> Arc<Mutex<()>> {
+    static LOCKS: Mutex<BTreeMap<PathBuf, Arc<Mutex<()>>>> = Mutex::new(BTreeMap::new());
+    let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut locks = LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    // Drop the locks no save holds any more.
+    locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+    locks.entry(key).or_default().clone()
+}
+
+/// Writes the editor fields to `path` and returns the new version.
+///
+/// With `expected`, the file must still have that content; otherwise the
+/// save is refused with [`Error::Conflict`]. `base` is the content the
+/// editor read, used for the conflict's diffs; without it the local changes
+/// are diffed against the disk.
+pub fn save_skill_md(
+    path: &str,
+    frontmatter: &HashMap<String, String>,
+    body: &str,
+    expected: Option<&FileVersion>,
+    base: Option<&str>,
+) -> Result<FileVersion> {
+    let content = render_skill_md(frontmatter, body);
+    let lock = save_lock(Path::new(path));
+    let _saving = lock.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(expected) = expected {
+        let (disk_content, actual) = match read_versioned(path) {
+            Ok((content, version)) => (Some(content), Some(version)),
+            Err(e) if e.code() == ErrorCode::NotFound => (None, None),
+            Err(e) => return Err(e),
+        };
+        if !actual.as_ref().is_some_and(|actual| actual.same_content(expected)) {
+            let disk = disk_content.as_deref().unwrap_or_default();
+            let conflict = EditConflict {
+                expected: expected.clone(),
+                actual,
+                local_diff: unified_diff(base.unwrap_or(disk), &content, "opened", "editor"),
+                disk_diff: base.map(|base| unified_diff(base, disk, "opened", "disk")).unwrap_or_default(),
+                disk_content,
+            };
+            return Err(Error::Conflict {
+                path: path.into(),
+                details: serde_json::to_value(conflict)?,
+            });
+        }
+    }
+
+    // Ensure parent directory exists
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io("create", parent, e))?;
+    }
+
+    history::record(Path::new(path))?;
+    write_atomic(Path::new(path), &content)?;
+    let metadata = fs::metadata(path).map_err(|e| Error::io("read", path, e))?;
+    Ok(FileVersion::of(content.as_bytes(), &metadata))
+}
+
+fn format_error(message: &str) -> ValidationResult {
+    ValidationResult {
+        valid: false,
+        errors: vec![DiagnosticItem {
+            field: "format".into(),
+            message: message.into(),
+            severity: "error".into(),
+        }],
+        warnings: vec![],
+    }
+}
+
+/// Validates a SKILL.md file, applying the lint levels of the project it
+/// belongs to, if that project has a `.skillduck.toml`. A config that cannot
+/// be read is reported as an error on the skill.
+pub fn validate_skill(path: &str) -> Result<ValidationResult> {
+    let content = fs::read_to_string(path).map_err(|e| Error::io("read", path, e))?;
+
+    let dir_name = Path::new(path)
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let result = validate_skill_text(&content, &dir_name);
+    let scan_roots: Vec<PathBuf> = settings::current()
+        .map(|s| s.scan_roots.iter().map(PathBuf::from).collect())
+        .unwrap_or_default();
+    Ok(match ProjectConfig::find(Path::new(path), &scan_roots) {
+        Ok(Some(config)) => config.apply_lint(result),
+        Ok(None) => result,
+        Err(e) => {
+            let mut result = result;
+            result.valid = false;
+            result.errors.push(DiagnosticItem {
+                field: "config".into(),
+                message: e.describe(),
+                severity: "error".into(),
+            });
+            result
+        }
+    })
+}
+
+/// Validates raw SKILL.md text against the agentskills.io specification.
+pub fn validate_skill_text(content: &str, dir_name: &str) -> ValidationResult {
+    let (yaml_str, body) = match split_frontmatter(content) {
+        Ok(parts) => parts,
+        Err(Error::UnclosedFrontmatter) => return format_error("Missing closing frontmatter delimiter"),
+        Err(_) => return format_error("Missing YAML frontmatter delimiters"),
+    };
+
+    let fm: HashMap<String, serde_yaml::Value> = match serde_yaml::from_str(yaml_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return ValidationResult {
+                valid: false,
+                errors: vec![DiagnosticItem {
+                    field: "frontmatter".into(),
+                    message: format!("Invalid YAML: {}", e),
+                    severity: "error".into(),
+                }],
+                warnings: vec![],
+            };
+        }
+    };
+
+    let name = fm
+        .get("name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let desc = fm
+        .get("description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    validate_fields(&name, &desc, dir_name, body, &fm)
+}
+
+pub fn validate_skill_content(
+    frontmatter: &HashMap<String, String>,
+    body: &str,
+    dir_name: &str,
+) -> ValidationResult {
+    let name = frontmatter.get("name").cloned().unwrap_or_default();
+    let desc = frontmatter.get("description").cloned().unwrap_or_default();
+    let compat = frontmatter.get("compatibility").cloned().unwrap_or_default();
+
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Name validation
+    validate_name(&name, &mut errors);
+
+    // Name must match directory
+    if !dir_name.is_empty() && !name.is_empty() && name != dir_name {
+        warnings.push(DiagnosticItem {
+            field: "name".into(),
+            message: format!(
+                "Name '{}' should match directory name '{}'",
+                name, dir_name
+            ),
+            severity: "warning".into(),
+        });
+    }
+
+    // Description validation
+    validate_description(&desc, &mut errors, &mut warnings);
+
+    // Compatibility
+    if !compat.is_empty() && compat.len() > 500 {
+        errors.push(DiagnosticItem {
+            field: "compatibility".into(),
+            message: "Compatibility must be <= 500 characters".into(),
+            severity: "error".into(),
+        });
+    }
+
+    validate_body(body, &mut warnings);
+
+    ValidationResult {
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+fn validate_body(body: &str, warnings: &mut Vec<DiagnosticItem>) {
+    let body_lines = body.lines().count();
+    if body_lines > 500 {
+        warnings.push(DiagnosticItem {
+            field: "body".into(),
+            message: format!("Body is {} lines (recommended < 500)", body_lines),
+            severity: "warning".into(),
+        });
+    }
+
+    let token_estimate = estimate_tokens(body);
+    if token_estimate > 5000 {
+        warnings.push(DiagnosticItem {
+            field: "body".into(),
+            message: format!(
+                "Estimated {} tokens (recommended < 5000)",
+                token_estimate
+            ),
+            severity: "warning".into(),
+        });
+    }
+}
+
+fn validate_fields(
+    name: &str,
+    desc: &str,
+    dir_name: &str,
+    body: &str,
+    fm: &HashMap<String, serde_yaml::Value>,
+) -> ValidationResult {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    validate_name(name, &mut errors);
+    validate_description(desc, &mut errors, &mut warnings);
+
+    // Name must match directory
+    if !dir_name.is_empty() && !name.is_empty() && name != dir_name {
+        errors.push(DiagnosticItem {
+            field: "name".into(),
+            message: format!(
+                "Name '{}' must match parent directory name '{}'",
+                name, dir_name
+            ),
+            severity: "error".into(),
+        });
+    }
+
+    // Compatibility
+    if let Some(compat) = fm.get("compatibility").and_then(|v| v.as_str()) {
+        if compat.len() > 500 {
+            errors.push(DiagnosticItem {
+                field: "compatibility".into(),
+                message: "Compatibility must be <= 500 characters".into(),
+                severity: "error".into(),
+            });
+        }
+    }
+
+    validate_body(body, &mut warnings);
+
+    ValidationResult {
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+fn validate_name(name: &str, errors: &mut Vec<DiagnosticItem>) {
+    if name.is_empty() {
+        errors.push(DiagnosticItem {
+            field: "name".into(),
+            message: "Name is required".into(),
+            severity: "error".into(),
+        });
+        return;
+    }
+
+    if name.len() > 64 {
+        errors.push(DiagnosticItem {
+            field: "name".into(),
+            message: "Name must be <= 64 characters".into(),
+            severity: "error".into(),
+        });
+    }
+
+    let name_re = Regex::new(r"^[a-z][a-z0-9]*(-[a-z0-9]+)*$").unwrap();
+    if !name_re.is_match(name) {
+        errors.push(DiagnosticItem {
+            field: "name".into(),
+            message: "Name must be lowercase alphanumeric with single hyphens".into(),
+            severity: "error".into(),
+        });
+    }
+
+    if name.contains("--") {
+        errors.push(DiagnosticItem {
+            field: "name".into(),
+            message: "Name must not contain consecutive hyphens".into(),
+            severity: "error".into(),
+        });
+    }
+}
+
+fn validate_description(desc: &str, errors: &mut Vec<DiagnosticItem>, warnings: &mut Vec<DiagnosticItem>) {
+    if desc.is_empty() {
+        errors.push(DiagnosticItem {
+            field: "description".into(),
+            message: "Description is required".into(),
+            severity: "error".into(),
+        });
+        return;
+    }
+
+    if desc.len() > 1024 {
+        errors.push(DiagnosticItem {
+            field: "description".into(),
+            message: "Description must be <= 1024 characters".into(),
+            severity: "error".into(),
+        });
+    }
+
+    if desc.len() < 20 {
+        warnings.push(DiagnosticItem {
+            field: "description".into(),
+            message: "Description should be more descriptive (>= 20 chars)".into(),
+            severity: "warning".into(),
+        });
+    }
+}
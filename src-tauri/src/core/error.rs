@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Stable, machine-readable error codes. Serialized as snake_case strings
+/// and safe for the frontend and scripts to match on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    Io,
+    HomeDirUnavailable,
+    InvalidSkill,
+    ParseError,
+    InvalidInput,
+    UnknownAgent,
+    NpxMissing,
+    SpawnFailed,
+    NotExecutable,
+    WatchFailed,
+    UpdaterFailed,
+    Conflict,
+    Internal,
+}
+
+/// Errors returned by the SkillDuck core.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Cannot determine home directory")]
+    HomeDirUnavailable,
+
+    #[error("Path does not exist: {}", .0.display())]
+    NotFound(PathBuf),
+
+    #[error("Failed to {action} {}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Invalid SKILL.md: missing frontmatter delimiters")]
+    MissingFrontmatter,
+
+    #[error("Invalid SKILL.md: missing closing frontmatter delimiter")]
+    UnclosedFrontmatter,
+
+    #[error("YAML parse error")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("JSON error")]
+    Json(#[from] serde_json::Error),
+
+    #[error("TOML parse error in {}", path.display())]
+    Toml {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("Unknown agent: {0}")]
+    UnknownAgent(String),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("npx not found; install Node.js to use the skills CLI")]
+    NpxNotFound,
+
+    #[error("{} is not executable; add a #! line and the executable bit, or choose an interpreter", .0.display())]
+    NotExecutable(PathBuf),
+
+    #[error("Failed to execute {program}")]
+    Spawn {
+        program: String,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Failed to watch {}", path.display())]
+    Watch {
+        path: PathBuf,
+        #[source]
+        source: notify::Error,
+    },
+
+    #[error("{0}")]
+    Internal(String),
+
+    #[error("{} changed on disk since it was opened", path.display())]
+    Conflict {
+        path: PathBuf,
+        /// What the frontend needs to resolve the conflict, such as the
+        /// editor's serialized `EditConflict`.
+        details: serde_json::Value,
+    },
+}
+
+impl Error {
+    pub fn io(action: &'static str, path: impl AsRef<Path>, source: io::Error) -> Self {
+        Error::Io {
+            action,
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::HomeDirUnavailable => ErrorCode::HomeDirUnavailable,
+            Error::NotFound(_) => ErrorCode::NotFound,
+            Error::Io { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => ErrorCode::NotFound,
+                io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+                io::ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+                _ => ErrorCode::Io,
+            },
+            Error::MissingFrontmatter | Error::UnclosedFrontmatter => ErrorCode::InvalidSkill,
+            Error::Yaml(_) | Error::Json(_) | Error::Toml { .. } => ErrorCode::ParseError,
+            Error::UnknownAgent(_) => ErrorCode::UnknownAgent,
+            Error::InvalidInput(_) => ErrorCode::InvalidInput,
+            Error::NpxNotFound => ErrorCode::NpxMissing,
+            Error::NotExecutable(_) => ErrorCode::NotExecutable,
+            Error::Spawn { source, .. } => match source.kind() {
+                io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+                _ => ErrorCode::SpawnFailed,
+            },
+            Error::Watch { .. } => ErrorCode::WatchFailed,
+            Error::Conflict { .. } => ErrorCode::Conflict,
+            Error::Internal(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// The filesystem path the error is about, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::NotFound(path)
+            | Error::NotExecutable(path)
+            | Error::Io { path, .. }
+            | Error::Watch { path, .. }
+            | Error::Toml { path, .. }
+            | Error::Conflict { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Structured data the frontend needs to act on the error, if any.
+    pub fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            Error::Conflict { details, .. } => Some(details.clone()),
+            _ => None,
+        }
+    }
+
+    /// Messages of the underlying errors, outermost first.
+    pub fn causes(&self) -> Vec<String> {
+        let mut causes = Vec::new();
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            causes.push(err.to_string());
+            source = err.source();
+        }
+        causes
+    }
+
+    /// The message followed by its causes, for reports that carry an error
+    /// as plain text.
+    pub fn describe(&self) -> String {
+        std::iter::once(self.to_string()).chain(self.causes()).collect::<Vec<_>>().join(": ")
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,119 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use super::{Error, Result};
+
+/// Directory names skipped when walking scan roots for project skills.
+pub const IGNORED_DIRS: &[&str] = &[
+    "node_modules", ".git", "target", "dist", "build",
+    ".next", ".nuxt", ".venv", "venv", "__pycache__",
+    ".cache", "vendor", "bower_components",
+];
+
+pub fn is_ignored_dir(name: &str) -> bool {
+    IGNORED_DIRS.contains(&name)
+}
+
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+    if !dst.exists() {
+        fs::create_dir_all(dst).map_err(|e| Error::io("create", dst, e))?;
+    }
+
+    for entry in fs::read_dir(src).map_err(|e| Error::io("read", src, e))? {
+        let entry = entry.map_err(|e| Error::io("read", src, e))?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+
+        if src_path.is_dir() {
+            copy_dir_recursive(&src_path, &dst_path)?;
+        } else {
+            fs::copy(&src_path, &dst_path).map_err(|e| Error::io("copy", &src_path, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves a file or directory, falling back to copy-and-delete when a plain
+/// rename is not possible (e.g. across filesystems).
+pub fn move_path(src: &Path, dst: &Path) -> Result<()> {
+    if fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+
+    if src.is_dir() {
+        copy_dir_recursive(src, dst)?;
+        fs::remove_dir_all(src).map_err(|e| Error::io("remove", src, e))
+    } else {
+        fs::copy(src, dst).map_err(|e| Error::io("copy", src, e))?;
+        fs::remove_file(src).map_err(|e| Error::io("remove", src, e))
+    }
+}
+
+/// Replaces `path` with `contents` so that readers, and the file after a
+/// crash, only ever see the old or the new content: the data is written to a
+/// temporary file next to it, flushed to disk and renamed over it. A symlink
+/// at `path` is kept and its target replaced.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    write_atomic_with(path, |file| {
+        file.write_all(contents.as_ref())
+            .map_err(|e| Error::io("write", path, e))
+    })
+}
+
+/// Like [`write_atomic`], for content produced by a writer (an archive, a
+/// copied file): `write` fills the temporary file, which replaces `path` only
+/// if it succeeds.
+pub fn write_atomic_with(path: &Path, write: impl FnOnce(&mut fs::File) -> Result<()>) -> Result<()> {
+    let target = if path.is_symlink() {
+        fs::canonicalize(path).map_err(|e| Error::io("resolve", path, e))?
+    } else {
+        path.to_path_buf()
+    };
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut temp = tempfile::Builder::new()
+        .prefix(".skillduck-")
+        .suffix(".tmp")
+        .tempfile_in(dir)
+        .map_err(|e| Error::io("create", dir, e))?;
+    // Set before writing, so `write` can still change them.
+    let permissions = fs::metadata(&target).map(|m| m.permissions()).ok().or_else(new_file_permissions);
+    if let Some(permissions) = permissions {
+        temp.as_file()
+            .set_permissions(permissions)
+            .map_err(|e| Error::io("write", temp.path(), e))?;
+    }
+    write(temp.as_file_mut())?;
+    temp.as_file().sync_all().map_err(|e| Error::io("write", temp.path(), e))?;
+    temp.persist(&target).map_err(|e| Error::io("replace", &target, e.error))?;
+    sync_dir(dir);
+    Ok(())
+}
+
+/// Temporary files are private; files that did not exist get the usual mode.
+#[cfg(unix)]
+fn new_file_permissions() -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(fs::Permissions::from_mode(0o644))
+}
+
+#[cfg(not(unix))]
+fn new_file_permissions() -> Option<fs::Permissions> {
+    None
+}
+
+/// Makes a rename in `dir` durable. Best effort: not every platform or
+/// filesystem supports syncing a directory.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
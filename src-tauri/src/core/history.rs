@@ -0,0 +1,136 @@
+//! Earlier versions of edited SKILL.md files.
+//!
+//! Before SkillDuck overwrites a SKILL.md, the content on disk is kept as
+//! `history/<key>/<saved_at>.md` in the data directory, where `<key>` is
+//! derived from the file's canonical path. The newest [`HISTORY_LIMIT`]
+//! versions of each file are kept.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::editor::{read_versioned, save_lock, FileVersion};
+use super::fs::write_atomic;
+use super::{paths, Error, Result};
+
+/// Versions kept per file.
+pub const HISTORY_LIMIT: usize = 20;
+
+/// Records which file a history directory belongs to.
+const PATH_FILE: &str = "path";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Identifies the version for [`restore_file_version`].
+    pub id: String,
+    /// When the version was replaced, in milliseconds since the Unix epoch.
+    pub saved_at: u64,
+    pub size: u64,
+    pub hash: String,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn hash_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn history_dir(path: &Path) -> Result<PathBuf> {
+    let key = hash_hex(canonical(path).to_string_lossy().as_bytes());
+    Ok(paths::data_subdir("history")?.join(&key[..16]))
+}
+
+/// Versions in `dir`, newest first.
+fn read_entries(dir: &Path) -> Result<Vec<HistoryEntry>> {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::io("read", dir, e)),
+    };
+
+    let mut entries = Vec::new();
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let Some(saved_at) = path
+            .file_stem()
+            .filter(|_| path.extension().is_some_and(|ext| ext == "md"))
+            .and_then(|stem| stem.to_str()?.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let content = fs::read(&path).map_err(|e| Error::io("read", &path, e))?;
+        entries.push(HistoryEntry {
+            id: saved_at.to_string(),
+            saved_at,
+            size: content.len() as u64,
+            hash: hash_hex(&content),
+        });
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.saved_at));
+    Ok(entries)
+}
+
+/// Keeps the current content of `path`, if there is a file, as the newest
+/// version in its history.
+pub fn record(path: &Path) -> Result<()> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(Error::io("read", path, e)),
+    };
+
+    let dir = history_dir(path)?;
+    let entries = read_entries(&dir)?;
+    if entries.first().is_some_and(|e| e.hash == hash_hex(&content)) {
+        return Ok(());
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| Error::io("create", &dir, e))?;
+    write_atomic(&dir.join(PATH_FILE), canonical(path).to_string_lossy().as_bytes())?;
+    let mut saved_at = now_ms().max(entries.first().map_or(0, |e| e.saved_at + 1));
+    while dir.join(format!("{}.md", saved_at)).exists() {
+        saved_at += 1;
+    }
+    write_atomic(&dir.join(format!("{}.md", saved_at)), &content)?;
+
+    for old in entries.iter().skip(HISTORY_LIMIT.saturating_sub(1)) {
+        let file = dir.join(format!("{}.md", old.id));
+        fs::remove_file(&file).map_err(|e| Error::io("remove", &file, e))?;
+    }
+    Ok(())
+}
+
+/// Lists the kept versions of `path`, newest first.
+pub fn list_file_history(path: &str) -> Result<Vec<HistoryEntry>> {
+    read_entries(&history_dir(Path::new(path))?)
+}
+
+/// Replaces `path` with the version `id` from its history. The content
+/// being replaced is kept in the history, so a restore can be undone.
+pub fn restore_file_version(path: &str, id: &str) -> Result<FileVersion> {
+    let file = Path::new(path);
+    let dir = history_dir(file)?;
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::InvalidInput(format!("Invalid version id: {}", id)));
+    }
+    let version = dir.join(format!("{}.md", id));
+    let content = fs::read(&version).map_err(|e| Error::io("read", &version, e))?;
+
+    let lock = save_lock(file);
+    let _saving = lock.lock().unwrap_or_else(|e| e.into_inner());
+    record(file)?;
+    write_atomic(file, content)?;
+    Ok(read_versioned(path)?.1)
+}
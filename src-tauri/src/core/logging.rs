@@ -0,0 +1,30 @@
+//! A [`log`] backend that writes SkillDuck's own messages to stderr.
+//!
+//! Failures in background work (watchers, cleanup) that have no caller to
+//! return an error to are logged instead.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Dependencies log too; only our own messages are of interest.
+        metadata.level() <= Level::Info && metadata.target().starts_with("skillduck")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logger; does nothing if one is already installed.
+pub fn init() {
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
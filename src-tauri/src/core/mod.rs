@@ -0,0 +1,28 @@
+//! Tauri-free core of SkillDuck.
+//!
+//! Everything here works on plain paths and returns [`Result`] with a typed
+//! [`Error`], so it can be used by the headless CLI, other tools and tests.
+//! The Tauri commands in `crate::commands` are thin wrappers around it.
+
+pub mod agents;
+pub mod cli_bridge;
+pub mod context;
+pub mod editor;
+pub mod error;
+pub mod fs;
+pub mod history;
+pub mod logging;
+pub mod paths;
+pub mod project_config;
+pub mod sandbox;
+pub mod settings;
+pub mod skill_set;
+pub mod skill_tests;
+pub mod skills;
+pub mod snapshot;
+pub mod sync;
+pub mod trash;
+pub mod trigger;
+pub mod watcher;
+
+pub use error::{Error, Result};
@@ -0,0 +1,184 @@
+//! Where SkillDuck keeps its own files.
+//!
+//! | | Linux | Other platforms | `SKILLDUCK_HOME` |
+//! |--------|----------------------------------------|--------------------------|--------------------------|
+//! | config | `$XDG_CONFIG_HOME/skillduck`           | `~/.skillduck`           | `$SKILLDUCK_HOME`        |
+//! | data   | `$XDG_DATA_HOME/skillduck`             | `~/.skillduck`           | `$SKILLDUCK_HOME`        |
+//! | cache  | `$XDG_CACHE_HOME/skillduck`            | `~/.skillduck/cache`     | `$SKILLDUCK_HOME/cache`  |
+//!
+//! `SKILLDUCK_HOME` keeps everything in one directory, for tests and portable
+//! installs. On Linux, files from the `~/.skillduck` used by older versions
+//! are moved to the XDG directories the first time one is needed;
+//! [`legacy_migration`] reports what was moved.
+
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::fs::move_path;
+use super::{Error, Result};
+
+/// Overrides every SkillDuck directory.
+pub const HOME_ENV: &str = "SKILLDUCK_HOME";
+
+const APP_NAME: &str = "skillduck";
+const LEGACY_DIR: &str = ".skillduck";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Config,
+    Data,
+    Cache,
+}
+
+fn home_override() -> Option<PathBuf> {
+    env::var_os(HOME_ENV).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// The XDG base directory for `kind`; `dirs` applies `$XDG_*_HOME` and the
+/// spec's defaults.
+fn xdg_dir(kind: Kind) -> Option<PathBuf> {
+    let base = match kind {
+        Kind::Config => dirs::config_dir(),
+        Kind::Data => dirs::data_dir(),
+        Kind::Cache => dirs::cache_dir(),
+    };
+    base.map(|dir| dir.join(APP_NAME))
+}
+
+fn resolve(kind: Kind) -> Result<PathBuf> {
+    if cfg!(target_os = "linux") && home_override().is_none() {
+        let home = dirs::home_dir().ok_or(Error::HomeDirUnavailable)?;
+        MIGRATION.get_or_init(|| migrate_legacy(&home.join(LEGACY_DIR)));
+    }
+    locate(kind)
+}
+
+/// Where the `kind` directory is, without creating it or moving legacy
+/// files into it.
+fn locate(kind: Kind) -> Result<PathBuf> {
+    if let Some(root) = home_override() {
+        return Ok(if kind == Kind::Cache { root.join("cache") } else { root });
+    }
+
+    let home = dirs::home_dir().ok_or(Error::HomeDirUnavailable)?;
+    if cfg!(target_os = "linux") {
+        return xdg_dir(kind).ok_or(Error::HomeDirUnavailable);
+    }
+
+    let legacy = home.join(LEGACY_DIR);
+    Ok(if kind == Kind::Cache { legacy.join("cache") } else { legacy })
+}
+
+/// An entry of the legacy `~/.skillduck` moved to the XDG directories.
+#[derive(Debug, Clone, Serialize)]
+pub struct LegacyMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Why the entry could not be moved; it is left where it was.
+    pub error: Option<String>,
+}
+
+static MIGRATION: OnceLock<Vec<LegacyMove>> = OnceLock::new();
+
+/// What was moved out of the legacy `~/.skillduck` when this process first
+/// needed a SkillDuck directory. Empty on other platforms, with
+/// `SKILLDUCK_HOME` set, or when there was nothing to move.
+pub fn legacy_migration() -> Result<Vec<LegacyMove>> {
+    resolve(Kind::Config)?;
+    Ok(MIGRATION.get().cloned().unwrap_or_default())
+}
+
+/// What [`legacy_migration`] would move, without moving anything. Empty
+/// once the migration has run in this process.
+pub fn pending_legacy_migration() -> Result<Vec<LegacyMove>> {
+    if !cfg!(target_os = "linux") || home_override().is_some() || MIGRATION.get().is_some() {
+        return Ok(Vec::new());
+    }
+    let home = dirs::home_dir().ok_or(Error::HomeDirUnavailable)?;
+    Ok(legacy_moves(&home.join(LEGACY_DIR))
+        .into_iter()
+        .map(|(from, to)| LegacyMove { from, to, error: None })
+        .collect())
+}
+
+/// Where the config file `name` is now, without creating the config
+/// directory or moving legacy files: in `~/.skillduck` while it is still
+/// waiting to be moved.
+pub fn peek_config_file(name: &str) -> Result<PathBuf> {
+    let path = locate(Kind::Config)?.join(name);
+    let pending = pending_legacy_migration()?;
+    Ok(pending.into_iter().find(|m| m.to == path).map(|m| m.from).unwrap_or(path))
+}
+
+/// The entries of `legacy` and where they go: the settings to the config
+/// directory and everything else to the data directory. Entries that
+/// already exist there are left out.
+fn legacy_moves(legacy: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let Ok(entries) = fs::read_dir(legacy) else {
+        return Vec::new();
+    };
+    let (Some(config), Some(data)) = (xdg_dir(Kind::Config), xdg_dir(Kind::Data)) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let dir = if name.to_string_lossy().starts_with("settings.json") { &config } else { &data };
+            let target = dir.join(&name);
+            (!target.exists()).then(|| (entry.path(), target))
+        })
+        .collect()
+}
+
+/// Moves the entries of `legacy` as [`legacy_moves`] lays out, and removes
+/// `legacy` once empty.
+fn migrate_legacy(legacy: &Path) -> Vec<LegacyMove> {
+    let report = legacy_moves(legacy)
+        .into_iter()
+        .map(|(from, to)| {
+            let dir = to.parent().unwrap_or(&to);
+            let moved = fs::create_dir_all(dir)
+                .map_err(|e| Error::io("create", dir, e))
+                .and_then(|_| move_path(&from, &to));
+            LegacyMove {
+                error: moved.err().map(|e| e.describe()),
+                from,
+                to,
+            }
+        })
+        .collect();
+    // Fails, as intended, if anything was left behind.
+    let _ = fs::remove_dir(legacy);
+    report
+}
+
+fn create(dir: PathBuf) -> Result<PathBuf> {
+    fs::create_dir_all(&dir).map_err(|e| Error::io("create", &dir, e))?;
+    Ok(dir)
+}
+
+/// Directory for settings, created on first use.
+pub fn config_dir() -> Result<PathBuf> {
+    create(resolve(Kind::Config)?)
+}
+
+/// Directory for snapshots, history, trash and other data worth keeping,
+/// created on first use.
+pub fn data_dir() -> Result<PathBuf> {
+    create(resolve(Kind::Data)?)
+}
+
+/// Directory for files that can be recreated, created on first use.
+pub fn cache_dir() -> Result<PathBuf> {
+    create(resolve(Kind::Cache)?)
+}
+
+/// Returns `<data_dir>/<name>`, creating it if needed.
+pub fn data_subdir(name: &str) -> Result<PathBuf> {
+    create(data_dir()?.join(name))
+}
@@ -0,0 +1,239 @@
+//! `.skillduck.toml`, a project's own SkillDuck configuration.
+//!
+//! ```toml
+//! # Agents the project targets; skills of other agents are not listed or synced.
+//! agents = ["claude-code", "cursor"]
+//!
+//! # Paths, relative to the project root and in .gitignore syntax, whose
+//! # skills SkillDuck leaves alone.
+//! ignore = ["experiments/", "vendor/**"]
+//!
+//! # Skills every target agent should have, and where to install them from.
+//! [[required_skills]]
+//! name = "pdf"
+//! source = "anthropics/skills"
+//!
+//! # Severity of validation diagnostics by field: "error", "warning" or "off".
+//! [lint]
+//! body = "error"
+//! compatibility = "off"
+//! ```
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::agents::get_agent_definitions;
+use super::editor::{DiagnosticItem, ValidationResult};
+use super::skills::Skill;
+use super::{Error, Result};
+
+pub const PROJECT_CONFIG_FILE: &str = ".skillduck.toml";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequiredSkill {
+    pub name: String,
+    /// Where to install the skill from, as accepted by `npx skills add`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Error,
+    Warning,
+    Off,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    /// Directory holding the config file.
+    #[serde(skip)]
+    pub root: PathBuf,
+    /// Agents the project targets; empty means every agent.
+    pub agents: Vec<String>,
+    pub required_skills: Vec<RequiredSkill>,
+    /// Severity of validation diagnostics, by the field they are about.
+    pub lint: BTreeMap<String, LintLevel>,
+    pub ignore: Vec<String>,
+    #[serde(skip)]
+    ignore_matcher: Option<Gitignore>,
+}
+
+impl ProjectConfig {
+    /// Reads `<project_root>/.skillduck.toml`, if there is one.
+    pub fn load(project_root: &Path) -> Result<Option<ProjectConfig>> {
+        let path = project_root.join(PROJECT_CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path).map_err(|e| Error::io("read", &path, e))?;
+        let mut config: ProjectConfig = toml::from_str(&content).map_err(|source| Error::Toml {
+            path: path.clone(),
+            source,
+        })?;
+        config.root = project_root.to_path_buf();
+
+        let mut builder = GitignoreBuilder::new(project_root);
+        for pattern in &config.ignore {
+            builder
+                .add_line(Some(path.clone()), pattern)
+                .map_err(|e| Error::InvalidInput(format!("Invalid ignore pattern '{}' in {}: {}", pattern, path.display(), e)))?;
+        }
+        config.ignore_matcher = Some(
+            builder
+                .build()
+                .map_err(|e| Error::InvalidInput(format!("Invalid ignore patterns in {}: {}", path.display(), e)))?,
+        );
+        Ok(Some(config))
+    }
+
+    /// The config of the nearest project containing `path`, looking no
+    /// further up than the root of that project (see [`project_root`]).
+    pub fn find(path: &Path, scan_roots: &[PathBuf]) -> Result<Option<ProjectConfig>> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let Some(root) = project_root(&path, scan_roots) else {
+            return Ok(None);
+        };
+        for dir in path.ancestors().skip(1).take_while(|dir| dir.starts_with(&root)) {
+            if let Some(config) = ProjectConfig::load(dir)? {
+                return Ok(Some(config));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn targets(&self, agent: &str) -> bool {
+        self.agents.is_empty() || self.agents.iter().any(|a| a == agent)
+    }
+
+    /// Whether `path`, inside the project, matches an ignore pattern.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Some(matcher) = &self.ignore_matcher else {
+            return false;
+        };
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        matcher.matched_path_or_any_parents(rel, path.is_dir()).is_ignore()
+    }
+
+    /// Drops skills installed under an ignored path.
+    pub fn filter_skills(&self, skills: Vec<Skill>) -> Vec<Skill> {
+        skills.into_iter().filter(|s| !self.is_ignored(Path::new(&s.install_path))).collect()
+    }
+
+    /// Applies the lint levels to a validation result.
+    pub fn apply_lint(&self, result: ValidationResult) -> ValidationResult {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        for item in result.errors.into_iter().chain(result.warnings) {
+            let level = match self.lint.get(&item.field) {
+                Some(level) => *level,
+                None if item.severity == "error" => LintLevel::Error,
+                None => LintLevel::Warning,
+            };
+            match level {
+                LintLevel::Error => errors.push(DiagnosticItem {
+                    severity: "error".into(),
+                    ..item
+                }),
+                LintLevel::Warning => warnings.push(DiagnosticItem {
+                    severity: "warning".into(),
+                    ..item
+                }),
+                LintLevel::Off => {}
+            }
+        }
+        ValidationResult {
+            valid: errors.is_empty(),
+            errors,
+            warnings,
+        }
+    }
+}
+
+/// The root of the project `path` is in: the directory holding the agent
+/// skills path `path` is under, such as `<root>/.claude/skills`, or else the
+/// innermost of `scan_roots` containing it.
+pub fn project_root(path: &Path, scan_roots: &[PathBuf]) -> Option<PathBuf> {
+    let agents = get_agent_definitions();
+    for dir in path.ancestors() {
+        for agent in &agents {
+            if dir.ends_with(&agent.project_path) {
+                let depth = Path::new(&agent.project_path).components().count();
+                return dir.ancestors().nth(depth).map(Path::to_path_buf);
+            }
+        }
+    }
+    scan_roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+}
+
+/// Project configs read during a scan, loaded once per project root. A
+/// config that cannot be read is treated as absent; validating the
+/// project's skills reports why (see [`validate_skill`]).
+///
+/// [`validate_skill`]: super::editor::validate_skill
+#[derive(Default)]
+pub struct ProjectConfigs(HashMap<PathBuf, Option<ProjectConfig>>);
+
+impl ProjectConfigs {
+    pub fn get(&mut self, project_root: &Path) -> Option<&ProjectConfig> {
+        self.0
+            .entry(project_root.to_path_buf())
+            .or_insert_with(|| ProjectConfig::load(project_root).ok().flatten())
+            .as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skill(install_path: &Path) -> Skill {
+        Skill::for_test("demo", &install_path.to_string_lossy())
+    }
+
+    #[test]
+    fn targets_listed_agents_or_all() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join(PROJECT_CONFIG_FILE), r#"agents = ["claude-code"]"#).unwrap();
+        let config = ProjectConfig::load(root.path()).unwrap().unwrap();
+        assert!(config.targets("claude-code"));
+        assert!(!config.targets("cursor"));
+
+        assert!(ProjectConfig::default().targets("cursor"));
+    }
+
+    #[test]
+    fn filters_skills_under_ignored_paths() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::write(root.join(PROJECT_CONFIG_FILE), r#"ignore = ["experiments/"]"#).unwrap();
+        let kept = root.join(".claude/skills/pdf");
+        let ignored = root.join("experiments/.claude/skills/draft");
+        fs::create_dir_all(&kept).unwrap();
+        fs::create_dir_all(&ignored).unwrap();
+
+        let config = ProjectConfig::load(root).unwrap().unwrap();
+        let skills = config.filter_skills(vec![skill(&kept), skill(&ignored)]);
+        let paths: Vec<&str> = skills.iter().map(|s| s.install_path.as_str()).collect();
+        assert_eq!(paths, [kept.to_string_lossy()]);
+    }
+
+    #[test]
+    fn rejects_an_unreadable_config() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join(PROJECT_CONFIG_FILE), "agents = [").unwrap();
+        assert!(matches!(ProjectConfig::load(root.path()), Err(Error::Toml { .. })));
+        assert!(ProjectConfigs::default().get(root.path()).is_none());
+    }
+}
@@ -0,0 +1,210 @@
+//! What a script did to its sandbox.
+//!
+//! The directories a script may write to, the workspace and the fake home,
+//! are captured before a run and compared with their state afterwards. Files
+//! are compared by size and modification time; small text files are also
+//! kept in memory so their changes can be shown as diffs.
+
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use super::{join_inside, sandbox_root};
+use crate::core::{Error, Result};
+
+/// Files up to this size get a text diff.
+const MAX_DIFF_BYTES: u64 = 64 * 1024;
+
+/// Files up to this size can be read with [`read_sandbox_file`].
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Path relative to the sandbox root.
+    pub path: String,
+    /// Size after the run; absent for deleted files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Size before the run; absent for created files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_size: Option<u64>,
+    /// Unified diff, for small text files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileChanges {
+    pub created: Vec<FileChange>,
+    pub modified: Vec<FileChange>,
+    pub deleted: Vec<FileChange>,
+}
+
+impl FileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+}
+
+struct FileState {
+    size: u64,
+    modified: Option<SystemTime>,
+    text: Option<String>,
+}
+
+/// The files of a sandbox at one point in time.
+pub struct TreeSnapshot {
+    root: PathBuf,
+    dirs: Vec<PathBuf>,
+    files: BTreeMap<String, FileState>,
+}
+
+fn read_small_text(path: &Path, size: u64) -> Option<String> {
+    if size > MAX_DIFF_BYTES {
+        return None;
+    }
+    let bytes = fs::read(path).ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+impl TreeSnapshot {
+    /// Captures the files under `dirs`, keyed by their path relative to `root`.
+    pub fn capture(root: &Path, dirs: &[PathBuf]) -> TreeSnapshot {
+        let mut files = BTreeMap::new();
+        let entries = dirs.iter().flat_map(|dir| WalkDir::new(dir).into_iter().filter_map(|e| e.ok()));
+        for entry in entries {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let Ok(rel) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+            files.insert(
+                rel.to_string_lossy().to_string(),
+                FileState {
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                    text: read_small_text(entry.path(), metadata.len()),
+                },
+            );
+        }
+        TreeSnapshot {
+            root: root.to_path_buf(),
+            dirs: dirs.to_vec(),
+            files,
+        }
+    }
+
+    /// Captures the tree again and compares it with this snapshot.
+    pub fn changes(&self) -> FileChanges {
+        let after = TreeSnapshot::capture(&self.root, &self.dirs);
+        let mut changes = FileChanges::default();
+
+        for (path, new) in &after.files {
+            match self.files.get(path) {
+                None => changes.created.push(FileChange {
+                    path: path.clone(),
+                    size: Some(new.size),
+                    previous_size: None,
+                    diff: new.text.as_deref().map(|text| unified_diff(path, "", text)),
+                }),
+                Some(old) => {
+                    let unchanged = match (&old.text, &new.text) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => old.size == new.size && old.modified == new.modified,
+                    };
+                    if unchanged {
+                        continue;
+                    }
+                    let diff = match (&old.text, &new.text) {
+                        (Some(a), Some(b)) => Some(unified_diff(path, a, b)),
+                        _ => None,
+                    };
+                    changes.modified.push(FileChange {
+                        path: path.clone(),
+                        size: Some(new.size),
+                        previous_size: Some(old.size),
+                        diff,
+                    });
+                }
+            }
+        }
+
+        for (path, old) in &self.files {
+            if !after.files.contains_key(path) {
+                changes.deleted.push(FileChange {
+                    path: path.clone(),
+                    size: None,
+                    previous_size: Some(old.size),
+                    diff: old.text.as_deref().map(|text| unified_diff(path, text, "")),
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxFile {
+    pub path: String,
+    pub size: u64,
+    /// File content; lossily decoded and cut at 1 MiB.
+    pub content: String,
+    pub binary: bool,
+    pub truncated: bool,
+}
+
+/// Reads a file from a sandbox. `path` is relative to the sandbox root, as in
+/// [`FileChange::path`].
+pub fn read_sandbox_file(sandbox_path: &str, path: &str) -> Result<SandboxFile> {
+    let root = sandbox_root(Path::new(sandbox_path))?;
+    let file_path = join_inside(&root, path)?;
+    if !file_path.is_file() {
+        return Err(Error::NotFound(file_path));
+    }
+
+    // Symlinks created by a script must not lead out of the sandbox.
+    let canonical = file_path.canonicalize().map_err(|e| Error::io("resolve", &file_path, e))?;
+    let canonical_root = root.canonicalize().map_err(|e| Error::io("resolve", &root, e))?;
+    if !canonical.starts_with(&canonical_root) {
+        return Err(Error::InvalidInput(format!("{} points outside the sandbox", path)));
+    }
+
+    let file = File::open(&canonical).map_err(|e| Error::io("open", &canonical, e))?;
+    let size = file.metadata().map_err(|e| Error::io("read", &canonical, e))?.len();
+    let mut bytes = Vec::new();
+    file.take(MAX_READ_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| Error::io("read", &canonical, e))?;
+
+    // A character cut in half at the read limit does not make the file binary.
+    let binary = bytes.contains(&0)
+        || std::str::from_utf8(&bytes).is_err_and(|e| e.error_len().is_some());
+
+    Ok(SandboxFile {
+        path: path.to_string(),
+        size,
+        content: String::from_utf8_lossy(&bytes).to_string(),
+        binary,
+        truncated: size > MAX_READ_BYTES,
+    })
+}
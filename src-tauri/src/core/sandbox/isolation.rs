@@ -0,0 +1,416 @@
+//! Process isolation for sandbox runs.
+//!
+//! On Linux, scripts run in fresh user, mount, IPC, PID and (by default)
+//! network namespaces, set up the way bubblewrap does it: the root filesystem
+//! is mounted read-only, the sandbox directory stays writable, `/tmp` is a
+//! private tmpfs and the real home directory is hidden behind an empty tmpfs.
+//! The script runs as PID 1 of its PID namespace with its own `/proc`, so it
+//! cannot see or signal processes outside the run. `HOME` always points at a
+//! fake home inside the sandbox.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::{Error, Result};
+
+/// Which isolation features were in effect for a run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IsolationReport {
+    /// `"namespaces"` when Linux namespaces were used, `"none"` otherwise.
+    pub backend: String,
+    pub read_only_root: bool,
+    pub writable_sandbox: bool,
+    pub private_tmp: bool,
+    pub network_disabled: bool,
+    pub home_hidden: bool,
+    pub fake_home: bool,
+    /// Whether the script ran in its own PID namespace.
+    pub pid_namespace: bool,
+    /// Why namespace isolation could not be used, or which part of it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unavailable_reason: Option<String>,
+}
+
+impl IsolationReport {
+    fn unisolated(reason: String) -> Self {
+        IsolationReport {
+            backend: "none".to_string(),
+            writable_sandbox: true,
+            fake_home: true,
+            unavailable_reason: Some(reason),
+            ..Default::default()
+        }
+    }
+}
+
+pub struct Isolation {
+    sandbox_root: PathBuf,
+    fake_home: PathBuf,
+    allow_network: bool,
+    exposed_dir: Option<PathBuf>,
+}
+
+impl Isolation {
+    /// Prepares isolation for a sandbox rooted at `sandbox_root`, creating its fake home.
+    pub fn new(sandbox_root: &Path, allow_network: bool) -> Result<Self> {
+        let fake_home = sandbox_root.join("home");
+        fs::create_dir_all(&fake_home).map_err(|e| Error::io("create", &fake_home, e))?;
+        Ok(Isolation {
+            sandbox_root: sandbox_root.to_path_buf(),
+            fake_home,
+            allow_network,
+            exposed_dir: None,
+        })
+    }
+
+    /// Keeps `dir` readable (read-only) inside the sandbox even when it lies
+    /// in the hidden home directory, e.g. an interpreter installed with nvm.
+    pub fn expose_dir(&mut self, dir: &Path) {
+        self.exposed_dir = Some(dir.to_path_buf());
+    }
+
+    pub fn fake_home(&self) -> &Path {
+        &self.fake_home
+    }
+
+    /// Configures `cmd` to start isolated in `cwd` and reports what will be in effect.
+    /// Falls back to running unisolated when namespaces are unavailable.
+    #[cfg(target_os = "linux")]
+    pub fn apply(&self, cmd: &mut Command, cwd: &Path) -> IsolationReport {
+        let home = dirs::home_dir().filter(|h| h.is_dir() && h.parent().is_some() && *h != self.sandbox_root);
+        // Some systems refuse a second `/proc` mount (e.g. containers that mask
+        // parts of it); run without the PID namespace there rather than unisolated.
+        let (pid_namespace, partial_reason) = match linux::probe(true) {
+            Ok(()) => (Ok(true), None),
+            Err(reason) => (linux::probe(false).map(|()| false), Some(reason)),
+        };
+        // Only something inside the hidden home needs mounting back.
+        let exposed = self
+            .exposed_dir
+            .as_deref()
+            .filter(|dir| home.as_deref().is_some_and(|home| dir.starts_with(home) && *dir != home));
+        let plan = pid_namespace.and_then(|pid_namespace| {
+            linux::Plan::new(&self.sandbox_root, cwd, home.as_deref(), exposed, !self.allow_network, pid_namespace)
+                .map(|plan| (plan, pid_namespace))
+                .map_err(|e| e.to_string())
+        });
+
+        match plan {
+            Ok((plan, pid_namespace)) => {
+                linux::install(cmd, plan);
+                IsolationReport {
+                    backend: "namespaces".to_string(),
+                    read_only_root: true,
+                    writable_sandbox: true,
+                    private_tmp: true,
+                    network_disabled: !self.allow_network,
+                    home_hidden: home.is_some(),
+                    fake_home: true,
+                    pid_namespace,
+                    unavailable_reason: partial_reason
+                        .filter(|_| !pid_namespace)
+                        .map(|reason| format!("No PID namespace, other processes are visible: {}", reason)),
+                }
+            }
+            Err(reason) => IsolationReport::unisolated(reason),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply(&self, _cmd: &mut Command, _cwd: &Path) -> IsolationReport {
+        IsolationReport::unisolated("Process isolation is only supported on Linux".to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::{CStr, CString};
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::process::CommandExt;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+    use std::ptr;
+    use std::sync::OnceLock;
+
+    /// `struct mount_attr` from `<linux/mount.h>`.
+    #[repr(C)]
+    struct MountAttr {
+        attr_set: u64,
+        attr_clr: u64,
+        propagation: u64,
+        userns_fd: u64,
+    }
+
+    const MOUNT_ATTR_RDONLY: u64 = 0x1;
+    const OPEN_TREE_CLONE: libc::c_uint = 0x1;
+    const MOVE_MOUNT_F_EMPTY_PATH: libc::c_uint = 0x4;
+
+    /// Everything the child needs, prepared up front so the pre-exec hook
+    /// does not allocate.
+    pub struct Plan {
+        unshare_flags: libc::c_int,
+        pid_namespace: bool,
+        uid_map: CString,
+        gid_map: CString,
+        sandbox_root: CString,
+        /// Ancestors of the sandbox root and the exposed directory, outermost
+        /// first, recreated on the tmpfs mounts.
+        mkdirs: Vec<CString>,
+        hide_home: Option<CString>,
+        exposed: Option<CString>,
+        cwd: CString,
+    }
+
+    fn cstring(path: &Path) -> io::Result<CString> {
+        CString::new(path.as_os_str().as_bytes())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains a NUL byte"))
+    }
+
+    impl Plan {
+        pub fn new(
+            sandbox_root: &Path,
+            cwd: &Path,
+            hide_home: Option<&Path>,
+            exposed: Option<&Path>,
+            disable_network: bool,
+            pid_namespace: bool,
+        ) -> io::Result<Self> {
+            let mut unshare_flags = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWIPC;
+            if disable_network {
+                unshare_flags |= libc::CLONE_NEWNET;
+            }
+            if pid_namespace {
+                unshare_flags |= libc::CLONE_NEWPID;
+            }
+
+            // SAFETY: getuid/getgid cannot fail.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+
+            let mut mkdirs = Vec::new();
+            for path in std::iter::once(sandbox_root).chain(exposed) {
+                let mut ancestors = path
+                    .ancestors()
+                    .filter(|p| p.parent().is_some())
+                    .map(cstring)
+                    .collect::<io::Result<Vec<_>>>()?;
+                ancestors.reverse();
+                mkdirs.extend(ancestors);
+            }
+
+            Ok(Plan {
+                unshare_flags,
+                pid_namespace,
+                uid_map: CString::new(format!("{} {} 1", uid, uid))?,
+                gid_map: CString::new(format!("{} {} 1", gid, gid))?,
+                sandbox_root: cstring(sandbox_root)?,
+                mkdirs,
+                hide_home: hide_home.map(cstring).transpose()?,
+                exposed: exposed.map(cstring).transpose()?,
+                cwd: cstring(cwd)?,
+            })
+        }
+    }
+
+    fn check(ret: libc::c_int) -> io::Result<()> {
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    unsafe fn write_file(path: &CStr, content: &[u8]) -> io::Result<()> {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        check(fd)?;
+        let written = libc::write(fd, content.as_ptr().cast(), content.len());
+        let err = io::Error::last_os_error();
+        libc::close(fd);
+        if written < 0 {
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    unsafe fn set_read_only(path: &CStr) -> io::Result<()> {
+        let attr = MountAttr {
+            attr_set: MOUNT_ATTR_RDONLY,
+            attr_clr: 0,
+            propagation: 0,
+            userns_fd: 0,
+        };
+        let ret = libc::syscall(
+            libc::SYS_mount_setattr,
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            libc::AT_RECURSIVE as libc::c_uint,
+            &attr as *const MountAttr,
+            std::mem::size_of::<MountAttr>(),
+        );
+        check(ret as libc::c_int)
+    }
+
+    unsafe fn mount_tmpfs(target: &CStr, options: &CStr) -> io::Result<()> {
+        check(libc::mount(
+            c"tmpfs".as_ptr(),
+            target.as_ptr(),
+            c"tmpfs".as_ptr(),
+            libc::MS_NOSUID | libc::MS_NODEV,
+            options.as_ptr().cast(),
+        ))
+    }
+
+    /// Detaches a copy of the mounts at `path`, to be attached elsewhere.
+    unsafe fn clone_tree(path: &CStr) -> io::Result<libc::c_int> {
+        let tree = libc::syscall(
+            libc::SYS_open_tree,
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            OPEN_TREE_CLONE | libc::O_CLOEXEC as libc::c_uint | libc::AT_RECURSIVE as libc::c_uint,
+        ) as libc::c_int;
+        check(tree)?;
+        Ok(tree)
+    }
+
+    /// Attaches a tree from [`clone_tree`] at `target` and closes it.
+    unsafe fn attach_tree(tree: libc::c_int, target: &CStr) -> io::Result<()> {
+        let moved = libc::syscall(
+            libc::SYS_move_mount,
+            tree,
+            c"".as_ptr(),
+            libc::AT_FDCWD,
+            target.as_ptr(),
+            MOVE_MOUNT_F_EMPTY_PATH,
+        ) as libc::c_int;
+        libc::close(tree);
+        check(moved)
+    }
+
+    /// Forks so the script becomes PID 1 of the new PID namespace, then mounts
+    /// that namespace's `/proc`. The first child only waits and exits like the
+    /// script did, so the spawned process still reports the script's status.
+    unsafe fn become_pid_one() -> io::Result<()> {
+        let pid = libc::fork();
+        check(pid)?;
+        if pid > 0 {
+            wait_and_exit(pid);
+        }
+
+        // Killing the waiting parent (as a kill of the run does) takes the
+        // whole namespace down with the script.
+        check(libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL as libc::c_ulong))?;
+        check(libc::mount(
+            c"proc".as_ptr(),
+            c"/proc".as_ptr(),
+            c"proc".as_ptr(),
+            libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+            ptr::null(),
+        ))
+    }
+
+    unsafe fn wait_and_exit(pid: libc::pid_t) -> ! {
+        // Keep no pipe open, so the spawner sees the script's exec and its
+        // output streams close when the script exits.
+        libc::syscall(libc::SYS_close_range, 0 as libc::c_uint, libc::c_uint::MAX, 0 as libc::c_uint);
+
+        let mut status = 0;
+        while libc::waitpid(pid, &mut status, 0) < 0 {
+            if io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+                libc::_exit(127);
+            }
+        }
+        if libc::WIFSIGNALED(status) {
+            let signal = libc::WTERMSIG(status);
+            libc::signal(signal, libc::SIG_DFL);
+            libc::kill(libc::getpid(), signal);
+            libc::_exit(128 + signal);
+        }
+        libc::_exit(libc::WEXITSTATUS(status))
+    }
+
+    /// Runs in the forked child right before exec. Only async-signal-safe calls.
+    unsafe fn enter(plan: &Plan) -> io::Result<()> {
+        check(libc::unshare(plan.unshare_flags))?;
+
+        // Older kernels have no setgroups file; the gid map works without it there.
+        if let Err(e) = write_file(c"/proc/self/setgroups", b"deny") {
+            if e.raw_os_error() != Some(libc::ENOENT) {
+                return Err(e);
+            }
+        }
+        write_file(c"/proc/self/uid_map", plan.uid_map.as_bytes())?;
+        write_file(c"/proc/self/gid_map", plan.gid_map.as_bytes())?;
+
+        check(libc::mount(
+            ptr::null(),
+            c"/".as_ptr(),
+            ptr::null(),
+            libc::MS_REC | libc::MS_PRIVATE,
+            ptr::null(),
+        ))?;
+
+        // Detach a writable copy of the sandbox before the root turns read-only
+        // and before `/tmp` gets covered.
+        let tree = clone_tree(&plan.sandbox_root)?;
+
+        set_read_only(c"/")?;
+
+        // Likewise a read-only copy of the exposed directory, before the home
+        // directory it lives in gets covered.
+        let exposed_tree = match &plan.exposed {
+            Some(dir) => Some((clone_tree(dir)?, dir)),
+            None => None,
+        };
+
+        if let Some(home) = &plan.hide_home {
+            mount_tmpfs(home, c"mode=0755")?;
+        }
+        mount_tmpfs(c"/tmp", c"mode=1777")?;
+
+        // The sandbox may now be covered by one of the tmpfs mounts; recreate
+        // its path and attach the detached copy there.
+        for dir in &plan.mkdirs {
+            libc::mkdir(dir.as_ptr(), 0o755);
+        }
+        attach_tree(tree, &plan.sandbox_root)?;
+        if let Some((tree, dir)) = exposed_tree {
+            attach_tree(tree, dir)?;
+        }
+
+        if plan.pid_namespace {
+            become_pid_one()?;
+        }
+
+        // The working directory was entered before the mounts changed.
+        check(libc::chdir(plan.cwd.as_ptr()))
+    }
+
+    pub fn install(cmd: &mut Command, plan: Plan) {
+        // SAFETY: `enter` only makes raw syscalls on data prepared before the fork.
+        unsafe {
+            cmd.pre_exec(move || enter(&plan));
+        }
+    }
+
+    /// Checks once whether this system lets us create the namespaces, with or
+    /// without a PID namespace.
+    pub fn probe(pid_namespace: bool) -> Result<(), String> {
+        static PROBES: [OnceLock<Result<(), String>>; 2] = [OnceLock::new(), OnceLock::new()];
+        PROBES[pid_namespace as usize]
+            .get_or_init(|| {
+                let dir = tempfile::tempdir().map_err(|e| e.to_string())?;
+                let plan = Plan::new(dir.path(), dir.path(), None, None, true, pid_namespace).map_err(|e| e.to_string())?;
+                let mut cmd = Command::new("true");
+                cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+                install(&mut cmd, plan);
+                match cmd.status() {
+                    Ok(status) if status.success() => Ok(()),
+                    Ok(status) => Err(format!("Namespace probe failed with {}", status)),
+                    Err(e) => Err(format!("Cannot create namespaces: {}", e)),
+                }
+            })
+            .clone()
+    }
+}
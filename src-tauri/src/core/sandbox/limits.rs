@@ -0,0 +1,251 @@
+//! Resource limits for sandbox runs.
+//!
+//! Scripts run in their own process group. Wall time, memory, process count,
+//! total CPU time and output size are checked by polling the run's processes
+//! (the group plus everything descended from the script, so a `setsid` does
+//! not escape) while it runs; all of them are killed on the first breach.
+//! Per-process CPU time (with a second of slack) and, inside a user
+//! namespace, the process count are also capped with rlimits so a breach
+//! between two polls cannot run away.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::Duration;
+
+/// How often a running script is checked against its limits.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Unknown keys are refused, so a misspelled limit is not silently left at
+/// its default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResourceLimits {
+    pub wall_time_ms: u64,
+    /// CPU time summed over every process of the run.
+    pub cpu_time_ms: u64,
+    /// Resident memory summed over every process of the run.
+    pub memory_bytes: u64,
+    pub max_processes: u32,
+    /// Combined size of stdout and stderr.
+    pub max_output_bytes: u64,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        ResourceLimits {
+            wall_time_ms: 60_000,
+            cpu_time_ms: 30_000,
+            memory_bytes: 1024 * 1024 * 1024,
+            max_processes: 64,
+            max_output_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    WallTime,
+    CpuTime,
+    Memory,
+    Processes,
+    Output,
+}
+
+impl LimitKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            LimitKind::WallTime => "wall time",
+            LimitKind::CpuTime => "CPU time",
+            LimitKind::Memory => "memory",
+            LimitKind::Processes => "process",
+            LimitKind::Output => "output",
+        }
+    }
+}
+
+/// What the processes of a run are using right now.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub processes: u32,
+    pub rss_bytes: u64,
+    pub cpu_ms: u64,
+}
+
+impl ResourceLimits {
+    /// Returns the first limit exceeded, if any.
+    pub fn check(&self, elapsed: Duration, output_bytes: u64, usage: Option<Usage>) -> Option<LimitKind> {
+        if elapsed.as_millis() as u64 > self.wall_time_ms {
+            return Some(LimitKind::WallTime);
+        }
+        if output_bytes > self.max_output_bytes {
+            return Some(LimitKind::Output);
+        }
+        let usage = usage?;
+        if usage.rss_bytes > self.memory_bytes {
+            Some(LimitKind::Memory)
+        } else if usage.cpu_ms > self.cpu_time_ms {
+            Some(LimitKind::CpuTime)
+        } else if usage.processes > self.max_processes {
+            Some(LimitKind::Processes)
+        } else {
+            None
+        }
+    }
+}
+
+/// Puts the script in its own process group and sets its rlimits. The process
+/// count rlimit is only set inside a user namespace, where it counts the run's
+/// processes rather than every process of the user.
+#[cfg(unix)]
+pub fn configure(cmd: &mut Command, limits: &ResourceLimits, in_user_namespace: bool) {
+    use std::os::unix::process::CommandExt;
+
+    let cpu_secs = limits.cpu_time_ms.div_ceil(1000).max(1) as libc::rlim_t;
+    let processes = in_user_namespace.then_some(limits.max_processes as libc::rlim_t);
+
+    cmd.process_group(0);
+    // SAFETY: setrlimit is async-signal-safe and only reads stack values.
+    unsafe {
+        cmd.pre_exec(move || {
+            let cpu = libc::rlimit {
+                rlim_cur: cpu_secs + 1,
+                rlim_max: cpu_secs + 2,
+            };
+            if libc::setrlimit(libc::RLIMIT_CPU, &cpu) < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if let Some(processes) = processes {
+                let nproc = libc::rlimit {
+                    rlim_cur: processes,
+                    rlim_max: processes,
+                };
+                if libc::setrlimit(libc::RLIMIT_NPROC, &nproc) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn configure(_cmd: &mut Command, _limits: &ResourceLimits, _in_user_namespace: bool) {}
+
+/// Kills every process in the group led by `pid` and every descendant of `pid`.
+#[cfg(target_os = "linux")]
+pub fn kill_tree(pid: u32) {
+    let processes = run_processes(pid).unwrap_or_default();
+    // SAFETY: sending a signal has no memory-safety requirements.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        for process in processes {
+            libc::kill(process.pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+}
+
+/// Kills every process in the group led by `pid`.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn kill_tree(pid: u32) {
+    // SAFETY: sending a signal has no memory-safety requirements.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn kill_tree(_pid: u32) {}
+
+/// Whether the script was stopped by the kernel for exceeding its CPU rlimit.
+#[cfg(unix)]
+pub fn hit_cpu_rlimit(status: &std::process::ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal() == Some(libc::SIGXCPU)
+}
+
+#[cfg(not(unix))]
+pub fn hit_cpu_rlimit(_status: &std::process::ExitStatus) -> bool {
+    false
+}
+
+/// One process as read from `/proc/<pid>/stat`.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy)]
+struct ProcessStat {
+    pid: u32,
+    ppid: u32,
+    pgid: u32,
+    /// utime, stime, cutime and cstime, in clock ticks.
+    ticks: u64,
+    rss_pages: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn read_stat(pid: u32) -> Option<ProcessStat> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name may contain spaces, so split after its closing paren.
+    let (_, rest) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |i: usize| fields.get(i).and_then(|f| f.parse::<u64>().ok()).unwrap_or(0);
+    Some(ProcessStat {
+        pid,
+        ppid: field(1) as u32,
+        pgid: field(2) as u32,
+        ticks: field(11) + field(12) + field(13) + field(14),
+        rss_pages: field(21),
+    })
+}
+
+/// The processes of the run started as `pid`: its process group, plus every
+/// process whose parent chain leads back to `pid`.
+#[cfg(target_os = "linux")]
+fn run_processes(pid: u32) -> Option<Vec<ProcessStat>> {
+    let all: Vec<ProcessStat> = std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(read_stat)
+        .collect();
+
+    let mut members = std::collections::HashSet::from([pid]);
+    members.extend(all.iter().filter(|p| p.pgid == pid).map(|p| p.pid));
+    // Add children of members until nothing changes; a process tree is
+    // shallow enough that this converges in a few passes.
+    loop {
+        let before = members.len();
+        for process in &all {
+            if members.contains(&process.ppid) {
+                members.insert(process.pid);
+            }
+        }
+        if members.len() == before {
+            break;
+        }
+    }
+
+    Some(all.into_iter().filter(|p| members.contains(&p.pid)).collect())
+}
+
+/// Sums up the processes of the run started as `pid` from `/proc`.
+#[cfg(target_os = "linux")]
+pub fn run_usage(pid: u32) -> Option<Usage> {
+    // SAFETY: sysconf has no memory-safety requirements.
+    let (ticks_per_sec, page_size) = unsafe { (libc::sysconf(libc::_SC_CLK_TCK), libc::sysconf(libc::_SC_PAGESIZE)) };
+    if ticks_per_sec <= 0 || page_size <= 0 {
+        return None;
+    }
+
+    let mut usage = Usage::default();
+    for process in run_processes(pid)? {
+        usage.processes += 1;
+        usage.cpu_ms += process.ticks * 1000 / ticks_per_sec as u64;
+        usage.rss_bytes += process.rss_pages * page_size as u64;
+    }
+    Some(usage)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn run_usage(_pid: u32) -> Option<Usage> {
+    None
+}
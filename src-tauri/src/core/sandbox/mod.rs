@@ -0,0 +1,164 @@
+//! Throwaway environments for trying out skills.
+//!
+//! A sandbox is a temp directory holding a `workspace/` (optionally seeded
+//! from a real project) with the skill installed under an agent's project
+//! skills path, a fake `home/` for scripts, and a `sandbox.json` describing it.
+//! Every sandbox is also recorded in a registry so it can be found and cleaned
+//! up after the app exits.
+
+mod changes;
+pub mod isolation;
+pub mod limits;
+mod registry;
+mod run;
+mod seed;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use self::changes::{read_sandbox_file, FileChange, FileChanges, SandboxFile, TreeSnapshot};
+use self::registry::sandbox_root;
+pub use self::registry::{cleanup_sandbox, cleanup_stale_sandboxes, list_sandboxes, SandboxStatus};
+pub use self::run::{
+    run_control, run_sandbox_script, stream_sandbox_script, Interpreter, OutputStream, RunControl, RunInput,
+    RunOptions, ScriptOutput,
+};
+pub use self::seed::{SeedMode, SeedStats};
+use super::agents::find_agent;
+use super::fs::{copy_dir_recursive, write_atomic};
+use super::{Error, Result};
+
+/// File at the root of every sandbox holding its [`SandboxInfo`].
+const MARKER_FILE: &str = "sandbox.json";
+
+/// Skills directory used when no agent is chosen.
+const DEFAULT_SKILLS_PATH: &str = ".agent/skills";
+
+/// Directory under the sandbox root that scripts work in.
+pub(crate) const WORKSPACE_DIR: &str = "workspace";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxInfo {
+    /// Name of the sandbox root directory.
+    pub id: String,
+    /// The installed skill directory; scripts run from here.
+    pub path: String,
+    /// The sandbox root, removed by `cleanup_sandbox`.
+    pub temp_dir_handle: String,
+    pub workspace: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<SeedStats>,
+    /// Unix time in milliseconds.
+    pub created_at: u64,
+    /// The process that created the sandbox.
+    pub owner_pid: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxOptions {
+    /// Project copied into the sandbox workspace.
+    pub project_dir: Option<String>,
+    pub seed_mode: SeedMode,
+    /// Agent whose project skills path the skill is installed under.
+    pub agent: Option<String>,
+}
+
+/// Joins a relative path onto `base`, refusing absolute paths and `..`.
+pub(crate) fn join_inside(base: &Path, relative: &str) -> Result<PathBuf> {
+    let rel = Path::new(relative);
+    if !rel.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(Error::InvalidInput(format!(
+            "Path must stay inside the sandbox: {}",
+            relative
+        )));
+    }
+    Ok(base.join(rel))
+}
+
+/// Creates a sandbox with the skill at `skill_path` installed into a fresh
+/// workspace, optionally seeded from a project.
+pub fn create_sandbox(skill_path: &str, options: &SandboxOptions) -> Result<SandboxInfo> {
+    let skill_file = Path::new(skill_path);
+    let skill_dir = if skill_file.is_file() {
+        skill_file
+            .parent()
+            .ok_or_else(|| Error::InvalidInput("Cannot determine skill directory".into()))?
+    } else {
+        skill_file
+    };
+
+    let skill_name = skill_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "test-skill".to_string());
+
+    let skills_path = match &options.agent {
+        Some(agent_id) => find_agent(agent_id)?.project_path,
+        None => DEFAULT_SKILLS_PATH.to_string(),
+    };
+
+    let temp_dir = tempfile::Builder::new()
+        .prefix("skillduck-sandbox-")
+        .tempdir()
+        .map_err(|e| Error::io("create", std::env::temp_dir(), e))?;
+    let workspace = temp_dir.path().join(WORKSPACE_DIR);
+    fs::create_dir_all(&workspace).map_err(|e| Error::io("create", &workspace, e))?;
+
+    let seed = match &options.project_dir {
+        Some(project) => Some(seed::seed_workspace(Path::new(project), &workspace, options.seed_mode)?),
+        None => None,
+    };
+
+    let sandbox_skill_dir = join_inside(&workspace, &skills_path)?.join(&skill_name);
+    if sandbox_skill_dir.exists() {
+        // The project may ship its own copy of the skill; the one under test wins.
+        fs::remove_dir_all(&sandbox_skill_dir).map_err(|e| Error::io("remove", &sandbox_skill_dir, e))?;
+    }
+    fs::create_dir_all(&sandbox_skill_dir).map_err(|e| Error::io("create", &sandbox_skill_dir, e))?;
+    copy_dir_recursive(skill_dir, &sandbox_skill_dir)?;
+
+    let info = SandboxInfo {
+        id: temp_dir.path().file_name().unwrap_or_default().to_string_lossy().to_string(),
+        path: sandbox_skill_dir.to_string_lossy().to_string(),
+        temp_dir_handle: temp_dir.path().to_string_lossy().to_string(),
+        workspace: workspace.to_string_lossy().to_string(),
+        agent: options.agent.clone(),
+        project_dir: options.project_dir.clone(),
+        seed,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default(),
+        owner_pid: std::process::id(),
+    };
+
+    let marker = temp_dir.path().join(MARKER_FILE);
+    write_atomic(&marker, serde_json::to_string_pretty(&info)?)?;
+    registry::register(&info)?;
+
+    // Only keep the directory once everything is in place.
+    let _ = temp_dir.keep();
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_inside_keeps_paths_inside() {
+        let base = Path::new("/sandbox/workspace");
+        assert_eq!(join_inside(base, "out/report.txt").unwrap(), base.join("out/report.txt"));
+        assert_eq!(join_inside(base, "./a").unwrap(), base.join("a"));
+        assert!(join_inside(base, "../secret").is_err());
+        assert!(join_inside(base, "out/../../secret").is_err());
+        assert!(join_inside(base, "/etc/passwd").is_err());
+    }
+}
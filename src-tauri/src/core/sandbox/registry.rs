@@ -0,0 +1,133 @@
+//! Record of every sandbox SkillDuck has created.
+//!
+//! Each sandbox gets `sandboxes/<id>.json` in the data directory holding its
+//! [`SandboxInfo`] and the process that created it. Only registered sandboxes
+//! can be cleaned up, and sandboxes whose process is gone are removed on the
+//! next launch.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::SandboxInfo;
+use crate::core::fs::write_atomic;
+use crate::core::{paths, Error, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxStatus {
+    #[serde(flatten)]
+    pub info: SandboxInfo,
+    /// The process that created the sandbox has exited, or its directory is gone.
+    pub stale: bool,
+}
+
+fn registry_dir() -> Result<PathBuf> {
+    paths::data_subdir("sandboxes")
+}
+
+fn record_path(id: &str) -> Result<PathBuf> {
+    Ok(registry_dir()?.join(format!("{}.json", id)))
+}
+
+pub(super) fn register(info: &SandboxInfo) -> Result<()> {
+    let path = record_path(&info.id)?;
+    write_atomic(&path, serde_json::to_string_pretty(info)?)
+}
+
+fn read_records() -> Result<Vec<(PathBuf, SandboxInfo)>> {
+    let dir = registry_dir()?;
+    let mut records = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| Error::io("read", &dir, e))?.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        match serde_json::from_str::<SandboxInfo>(&content) {
+            Ok(info) => records.push((path, info)),
+            // Unreadable records cannot lead anywhere; drop them.
+            Err(_) => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(unix)]
+fn process_running(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    // SAFETY: signal 0 only checks whether the process exists.
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_running(pid: u32) -> bool {
+    pid == std::process::id()
+}
+
+fn is_stale(info: &SandboxInfo) -> bool {
+    !Path::new(&info.temp_dir_handle).is_dir() || !process_running(info.owner_pid)
+}
+
+/// Lists registered sandboxes, newest first.
+pub fn list_sandboxes() -> Result<Vec<SandboxStatus>> {
+    let mut sandboxes: Vec<SandboxStatus> = read_records()?
+        .into_iter()
+        .map(|(_, info)| SandboxStatus {
+            stale: is_stale(&info),
+            info,
+        })
+        .collect();
+    sandboxes.sort_by_key(|s| std::cmp::Reverse(s.info.created_at));
+    Ok(sandboxes)
+}
+
+/// The root of the registered sandbox `sandbox_path` lies in. Paths outside
+/// every registered sandbox are refused, so nothing can be read or run from
+/// elsewhere on disk.
+pub(super) fn sandbox_root(sandbox_path: &Path) -> Result<PathBuf> {
+    // Resolved first so `..` or a symlink cannot pass for a path inside.
+    let path = sandbox_path
+        .canonicalize()
+        .map_err(|e| Error::io("resolve", sandbox_path, e))?;
+    read_records()?
+        .into_iter()
+        .map(|(_, info)| PathBuf::from(info.temp_dir_handle))
+        .find(|root| root.canonicalize().is_ok_and(|root| path.starts_with(root)))
+        .ok_or_else(|| Error::InvalidInput(format!("{} is not inside a SkillDuck sandbox", sandbox_path.display())))
+}
+
+/// Removes a registered sandbox and its record. Paths that are not the root
+/// of a registered sandbox are refused.
+pub fn cleanup_sandbox(temp_dir_handle: &str) -> Result<()> {
+    let (record, info) = read_records()?
+        .into_iter()
+        .find(|(_, info)| info.temp_dir_handle == temp_dir_handle)
+        .ok_or_else(|| Error::InvalidInput(format!("{} is not a SkillDuck sandbox", temp_dir_handle)))?;
+    remove(&record, &info)
+}
+
+fn remove(record: &Path, info: &SandboxInfo) -> Result<()> {
+    let path = Path::new(&info.temp_dir_handle);
+    if path.exists() {
+        fs::remove_dir_all(path).map_err(|e| Error::io("remove", path, e))?;
+    }
+    fs::remove_file(record).map_err(|e| Error::io("remove", record, e))
+}
+
+/// Removes every stale sandbox. Returns how many were removed.
+pub fn cleanup_stale_sandboxes() -> Result<usize> {
+    let mut removed = 0;
+    for (record, info) in read_records()? {
+        if is_stale(&info) && remove(&record, &info).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,425 @@
+//! Running scripts inside a sandbox.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{ChildStdin, Command as AsyncCommand};
+use tokio::sync::mpsc;
+
+use super::changes::{FileChanges, TreeSnapshot};
+use super::isolation::{Isolation, IsolationReport};
+use super::limits::{self, LimitKind, ResourceLimits};
+use super::{join_inside, sandbox_root, WORKSPACE_DIR};
+use crate::core::cli_bridge::find_program;
+use crate::core::{Error, Result};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScriptOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    pub isolation: IsolationReport,
+    /// The limit that stopped the script, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_hit: Option<LimitKind>,
+    /// Whether the run was stopped through [`RunControl::kill`].
+    pub killed: bool,
+    /// Files the script created, modified or deleted in the sandbox.
+    pub changes: FileChanges,
+}
+
+/// `PATH` inside the sandbox, after the interpreter's own directory and
+/// before any override from [`RunOptions::env`].
+const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunOptions {
+    /// Arguments passed to the script, as-is.
+    pub args: Vec<String>,
+    /// Extra environment variables, on top of `HOME` and `PATH`.
+    pub env: BTreeMap<String, String>,
+    /// Written to the script's stdin when it starts.
+    pub stdin: Option<String>,
+    /// Run the script with this interpreter instead of executing it directly.
+    pub interpreter: Option<Interpreter>,
+    /// Keep network access inside the sandbox.
+    pub allow_network: bool,
+    pub limits: ResourceLimits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpreter {
+    Python3,
+    Node,
+    Bash,
+    Deno,
+}
+
+impl Interpreter {
+    /// The program and the arguments that go before the script path.
+    fn command(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            Interpreter::Python3 => ("python3", &[]),
+            Interpreter::Node => ("node", &[]),
+            Interpreter::Bash => ("bash", &[]),
+            // The sandbox does the confining; Deno's own prompts would block on stdin.
+            Interpreter::Deno => ("deno", &["run", "--allow-all"]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+enum Control {
+    Stdin(Vec<u8>),
+    CloseStdin,
+    Kill,
+}
+
+/// Handle for writing to or killing a running script.
+#[derive(Debug, Clone)]
+pub struct RunControl(mpsc::UnboundedSender<Control>);
+
+/// Receiving end of a [`RunControl`], handed to [`stream_sandbox_script`].
+/// Once every `RunControl` is dropped the script's stdin is closed.
+pub struct RunInput(mpsc::UnboundedReceiver<Control>);
+
+pub fn run_control() -> (RunControl, RunInput) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (RunControl(tx), RunInput(rx))
+}
+
+impl RunControl {
+    fn send(&self, control: Control) -> Result<()> {
+        self.0
+            .send(control)
+            .map_err(|_| Error::InvalidInput("The run has already finished".into()))
+    }
+
+    pub fn write_stdin(&self, data: impl Into<Vec<u8>>) -> Result<()> {
+        self.send(Control::Stdin(data.into()))
+    }
+
+    pub fn close_stdin(&self) -> Result<()> {
+        self.send(Control::CloseStdin)
+    }
+
+    pub fn kill(&self) -> Result<()> {
+        self.send(Control::Kill)
+    }
+}
+
+/// Decodes a byte stream as UTF-8 across chunk boundaries.
+#[derive(Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    fn push(&mut self, data: &[u8]) -> String {
+        self.pending.extend_from_slice(data);
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            // An incomplete character at the end waits for the next chunk.
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        let rest = self.pending.split_off(complete);
+        let text = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending = rest;
+        text
+    }
+
+    fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending.clear();
+        text
+    }
+}
+
+/// Runs a script from the sandbox and returns its buffered output.
+pub async fn run_sandbox_script(
+    sandbox_path: &str,
+    script_name: &str,
+    options: &RunOptions,
+) -> Result<ScriptOutput> {
+    let (_, input) = run_control();
+    stream_sandbox_script(sandbox_path, script_name, options, input, |_, _| {}).await
+}
+
+/// Runs a script from the sandbox, isolated from the rest of the system where
+/// supported, passing output to `on_output` as it arrives. The returned
+/// summary holds the same output, up to the output limit.
+pub async fn stream_sandbox_script<F>(
+    sandbox_path: &str,
+    script_name: &str,
+    options: &RunOptions,
+    input: RunInput,
+    mut on_output: F,
+) -> Result<ScriptOutput>
+where
+    F: FnMut(OutputStream, &str) + Send,
+{
+    let sandbox_dir = Path::new(sandbox_path);
+    let root = sandbox_root(sandbox_dir)?;
+    let script_path = resolve_script(sandbox_dir, script_name)?;
+
+    let mut isolation = Isolation::new(&root, options.allow_network)?;
+    let mut path = DEFAULT_PATH.to_string();
+
+    let (mut command, program) = match options.interpreter {
+        Some(interpreter) => {
+            let (name, interpreter_args) = interpreter.command();
+            // Resolved on the host: version managers install under the home
+            // directory, which the sandbox hides and the default PATH misses.
+            let program = tokio::task::spawn_blocking(move || find_program(name))
+                .await
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| name.to_string());
+            let bin_dir = Path::new(&program)
+                .canonicalize()
+                .ok()
+                .and_then(|p| p.parent().map(Path::to_path_buf));
+            if let Some(bin_dir) = bin_dir {
+                // Expose the whole installation (its `lib/` next to `bin/`).
+                let install_dir = match bin_dir.file_name() {
+                    Some(name) if name == "bin" => bin_dir.parent().unwrap_or(&bin_dir),
+                    _ => &bin_dir,
+                };
+                isolation.expose_dir(install_dir);
+                if !DEFAULT_PATH.split(':').any(|dir| Path::new(dir) == bin_dir) {
+                    path = format!("{}:{}", bin_dir.display(), path);
+                }
+            }
+            let mut command = Command::new(&program);
+            command.args(interpreter_args).arg(&script_path);
+            (command, program)
+        }
+        None => {
+            ensure_executable(&script_path)?;
+            (Command::new(&script_path), script_path.display().to_string())
+        }
+    };
+    command
+        .args(&options.args)
+        .current_dir(sandbox_dir)
+        .env_clear()
+        .env("HOME", isolation.fake_home())
+        .env("PATH", path)
+        .envs(&options.env);
+    let isolation_report = isolation.apply(&mut command, sandbox_dir);
+    limits::configure(&mut command, &options.limits, isolation_report.backend == "namespaces");
+    command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    // The rest of the root, such as `sandbox.json`, is not the script's to change.
+    let dirs = vec![root.join(WORKSPACE_DIR), isolation.fake_home().to_path_buf()];
+    let before = tokio::task::spawn_blocking(move || TreeSnapshot::capture(&root, &dirs))
+        .await
+        .map_err(|e| Error::Internal(e.to_string()))?;
+    let start = Instant::now();
+
+    let mut child = AsyncCommand::from(command)
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| spawn_error(e, &script_path, program))?;
+    let pid = child.id().unwrap_or_default();
+
+    let (chunk_tx, mut chunks) = mpsc::unbounded_channel();
+    tokio::spawn(forward_output(child.stdout.take(), OutputStream::Stdout, chunk_tx.clone()));
+    tokio::spawn(forward_output(child.stderr.take(), OutputStream::Stderr, chunk_tx));
+    let mut stdin = child.stdin.take().map(spawn_stdin_writer);
+    if let (Some(stdin), Some(data)) = (&stdin, &options.stdin) {
+        let _ = stdin.send(data.clone().into_bytes());
+    }
+
+    let max_output = options.limits.max_output_bytes;
+    let mut output_bytes = 0u64;
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let (mut stdout_text, mut stderr_text) = (Utf8Decoder::default(), Utf8Decoder::default());
+
+    let mut input = input.0;
+    let mut input_open = true;
+    let mut outputs_open = true;
+    let mut status = None;
+    let mut limit_hit = None;
+    let mut killed = false;
+    let mut poll = tokio::time::interval(limits::POLL_INTERVAL);
+
+    while status.is_none() || outputs_open {
+        tokio::select! {
+            result = child.wait(), if status.is_none() => {
+                // Background processes left behind would keep the output pipes open.
+                limits::kill_tree(pid);
+                status = Some(result.map_err(|e| Error::io("wait for", &script_path, e))?);
+            }
+            chunk = chunks.recv(), if outputs_open => {
+                let Some((stream, data)) = chunk else {
+                    outputs_open = false;
+                    continue;
+                };
+                let keep = (max_output.saturating_sub(output_bytes) as usize).min(data.len());
+                output_bytes += data.len() as u64;
+                let (buffer, decoder) = match stream {
+                    OutputStream::Stdout => (&mut stdout, &mut stdout_text),
+                    OutputStream::Stderr => (&mut stderr, &mut stderr_text),
+                };
+                buffer.extend_from_slice(&data[..keep]);
+                let text = decoder.push(&data[..keep]);
+                if !text.is_empty() {
+                    on_output(stream, &text);
+                }
+                if output_bytes > max_output && limit_hit.is_none() {
+                    limit_hit = Some(LimitKind::Output);
+                    if status.is_none() {
+                        stop(&mut child, pid);
+                    }
+                }
+            }
+            control = input.recv(), if input_open && status.is_none() => match control {
+                Some(Control::Stdin(data)) => {
+                    if let Some(stdin) = &stdin {
+                        let _ = stdin.send(data);
+                    }
+                }
+                Some(Control::CloseStdin) => stdin = None,
+                Some(Control::Kill) => {
+                    killed = true;
+                    stop(&mut child, pid);
+                }
+                None => {
+                    input_open = false;
+                    stdin = None;
+                }
+            },
+            _ = poll.tick(), if status.is_none() && limit_hit.is_none() && !killed => {
+                let usage = limits::run_usage(pid);
+                limit_hit = options.limits.check(start.elapsed(), output_bytes, usage);
+                if limit_hit.is_some() {
+                    stop(&mut child, pid);
+                }
+            }
+        }
+    }
+
+    let duration = start.elapsed();
+    let status = status.expect("loop exits only after the child was reaped");
+
+    for (stream, decoder) in [(OutputStream::Stdout, &mut stdout_text), (OutputStream::Stderr, &mut stderr_text)] {
+        let text = decoder.finish();
+        if !text.is_empty() {
+            on_output(stream, &text);
+        }
+    }
+
+    // The script may have finished between two polls.
+    if limit_hit.is_none() && !killed && limits::hit_cpu_rlimit(&status) {
+        limit_hit = Some(LimitKind::CpuTime);
+    }
+
+    let changes = tokio::task::spawn_blocking(move || before.changes())
+        .await
+        .map_err(|e| Error::Internal(e.to_string()))?;
+
+    Ok(ScriptOutput {
+        stdout: String::from_utf8_lossy(&stdout).to_string(),
+        stderr: String::from_utf8_lossy(&stderr).to_string(),
+        exit_code: status.code().unwrap_or(-1),
+        duration_ms: duration.as_millis() as u64,
+        isolation: isolation_report,
+        limit_hit,
+        killed,
+        changes,
+    })
+}
+
+/// Resolves `script_name` inside the sandbox, refusing paths that lead out of it.
+fn resolve_script(sandbox_dir: &Path, script_name: &str) -> Result<PathBuf> {
+    let script_path = join_inside(sandbox_dir, script_name)?;
+    if !script_path.is_file() {
+        return Err(Error::NotFound(script_path));
+    }
+    Ok(script_path)
+}
+
+#[cfg(unix)]
+fn ensure_executable(script_path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = std::fs::metadata(script_path).map_err(|e| Error::io("read", script_path, e))?;
+    if metadata.permissions().mode() & 0o111 == 0 {
+        return Err(Error::NotExecutable(script_path.to_path_buf()));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn ensure_executable(_script_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn spawn_error(e: std::io::Error, script_path: &Path, program: String) -> Error {
+    // A missing or unknown #! line surfaces as an exec format error.
+    if is_exec_format_error(&e) {
+        Error::NotExecutable(script_path.to_path_buf())
+    } else {
+        Error::Spawn { program, source: e }
+    }
+}
+
+#[cfg(unix)]
+fn is_exec_format_error(e: &std::io::Error) -> bool {
+    e.raw_os_error() == Some(libc::ENOEXEC)
+}
+
+#[cfg(not(unix))]
+fn is_exec_format_error(_e: &std::io::Error) -> bool {
+    false
+}
+
+fn stop(child: &mut tokio::process::Child, pid: u32) {
+    limits::kill_tree(pid);
+    let _ = child.start_kill();
+}
+
+/// Sends everything read from a child pipe to `tx` until the pipe closes.
+async fn forward_output<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    stream: OutputStream,
+    tx: mpsc::UnboundedSender<(OutputStream, Vec<u8>)>,
+) {
+    let Some(mut reader) = reader else {
+        return;
+    };
+    let mut buf = [0u8; 8192];
+    while let Ok(n) = reader.read(&mut buf).await {
+        if n == 0 || tx.send((stream, buf[..n].to_vec())).is_err() {
+            break;
+        }
+    }
+}
+
+/// Writes whatever arrives on the returned channel to the child's stdin.
+/// Dropping the sender closes stdin.
+fn spawn_stdin_writer(mut stdin: ChildStdin) -> mpsc::UnboundedSender<Vec<u8>> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    tokio::spawn(async move {
+        while let Some(data) = rx.recv().await {
+            if stdin.write_all(&data).await.is_err() || stdin.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+    tx
+}
@@ -0,0 +1,126 @@
+//! Seeding a sandbox workspace from a real project.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::core::fs::is_ignored_dir;
+#[cfg(target_os = "linux")]
+use crate::core::fs::write_atomic_with;
+use crate::core::{Error, Result};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedMode {
+    /// Plain copies of every file.
+    #[default]
+    Copy,
+    /// Copy-on-write clones where the filesystem supports them (Btrfs, XFS,
+    /// APFS), plain copies elsewhere.
+    Clone,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SeedStats {
+    pub files: u64,
+    /// Files shared copy-on-write with the project instead of copied.
+    pub cloned: u64,
+    pub bytes: u64,
+}
+
+/// Copies `project` into `workspace`, skipping what `.gitignore`/`.ignore`
+/// files exclude and the usual dependency and build directories.
+pub fn seed_workspace(project: &Path, workspace: &Path, mode: SeedMode) -> Result<SeedStats> {
+    if !project.is_dir() {
+        return Err(Error::NotFound(project.to_path_buf()));
+    }
+
+    let mut stats = SeedStats::default();
+    let walker = ignore::WalkBuilder::new(project)
+        .hidden(false)
+        .git_global(false)
+        .require_git(false)
+        .filter_entry(|entry| {
+            !(entry.file_type().is_some_and(|t| t.is_dir())
+                && is_ignored_dir(&entry.file_name().to_string_lossy()))
+        })
+        .build();
+
+    for entry in walker {
+        let entry = entry.map_err(|e| Error::io("read", project, io::Error::other(e)))?;
+        let Ok(rel) = entry.path().strip_prefix(project) else {
+            continue;
+        };
+        let target = workspace.join(rel);
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| Error::io("create", &target, e))?;
+        } else if file_type.is_file() {
+            let cloned = mode == SeedMode::Clone && clone_file(entry.path(), &target).is_ok();
+            if cloned {
+                stats.cloned += 1;
+            } else {
+                fs::copy(entry.path(), &target).map_err(|e| Error::io("copy", entry.path(), e))?;
+            }
+            stats.files += 1;
+            stats.bytes += entry.metadata().map(|m| m.len()).unwrap_or_default();
+        } else if file_type.is_symlink() {
+            copy_symlink(entry.path(), &target)?;
+        }
+    }
+
+    Ok(stats)
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> Result<()> {
+    let link = fs::read_link(src).map_err(|e| Error::io("read", src, e))?;
+    std::os::unix::fs::symlink(link, dst).map_err(|e| Error::io("link", dst, e))
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(_src: &Path, _dst: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn clone_file(src: &Path, dst: &Path) -> Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let source = fs::File::open(src).map_err(|e| Error::io("open", src, e))?;
+    let permissions = source.metadata().map_err(|e| Error::io("read", src, e))?.permissions();
+    // A clone that fails leaves nothing behind at `dst`.
+    write_atomic_with(dst, |target| {
+        // SAFETY: both descriptors are open for the duration of the call.
+        if unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) } < 0 {
+            return Err(Error::io("clone", src, io::Error::last_os_error()));
+        }
+        target.set_permissions(permissions).map_err(|e| Error::io("copy", src, e))
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn clone_file(src: &Path, dst: &Path) -> Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let to_c = |p: &Path| {
+        CString::new(p.as_os_str().as_bytes())
+            .map_err(|_| Error::io("clone", src, io::Error::from(io::ErrorKind::InvalidInput)))
+    };
+    let (from, to) = (to_c(src)?, to_c(dst)?);
+    // SAFETY: both paths are valid NUL-terminated strings.
+    if unsafe { libc::clonefile(from.as_ptr(), to.as_ptr(), 0) } < 0 {
+        return Err(Error::io("clone", src, io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn clone_file(src: &Path, _dst: &Path) -> Result<()> {
+    Err(Error::io("clone", src, io::Error::from(io::ErrorKind::Unsupported)))
+}
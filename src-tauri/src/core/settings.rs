@@ -0,0 +1,380 @@
+//! `settings.json` in the config directory (see [`paths`]).
+//!
+//! The file carries a `version`. Older files are brought up to
+//! [`SETTINGS_VERSION`] by [`MIGRATIONS`] before they are parsed, missing
+//! fields take their defaults, and fields this build does not know (written
+//! by a newer one) are kept when saving. A file that cannot be parsed when
+//! the settings are first read is backed up and replaced with the defaults.
+//!
+//! [`current`] serves the settings from memory; [`save_settings`] and
+//! [`reload`] keep that copy in step with the file, and [`watch_settings`]
+//! reloads it when the file is edited outside SkillDuck. An edit that leaves
+//! the file unparseable is reported and otherwise ignored, so the user can
+//! fix it.
+
+use notify::{recommended_watcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex, RwLock, RwLockWriteGuard};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::fs::write_atomic;
+use super::watcher::DEBOUNCE;
+use super::{paths, Error, Result};
+
+/// Version of the settings written by this build.
+pub const SETTINGS_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub scan_roots: Vec<String>,
+    /// Home directories whose agents' global skills are listed besides the
+    /// current user's, such as a dev container's or a CI user's.
+    pub home_roots: Vec<String>,
+    /// Fields from a newer version, kept as they are.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: SETTINGS_VERSION,
+            scan_roots: Vec::new(),
+            home_roots: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` turns version `n` settings into version `n + 1`.
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] = [migrate_v0];
+
+/// Version 0 is the unversioned `{ "scan_roots": [...] }`. Blank and
+/// repeated scan roots are dropped.
+fn migrate_v0(settings: &mut Map<String, Value>) {
+    if let Some(Value::Array(roots)) = settings.get_mut("scan_roots") {
+        let mut seen = Vec::new();
+        roots.retain(|root| match root.as_str().map(str::trim) {
+            Some(root) if !root.is_empty() && !seen.contains(&root.to_string()) => {
+                seen.push(root.to_string());
+                true
+            }
+            _ => false,
+        });
+    }
+}
+
+/// Parses settings of any version up to [`SETTINGS_VERSION`]; newer
+/// versions are read as far as this build understands them.
+pub fn parse_settings(content: &str) -> Result<Settings> {
+    let mut value: Value = serde_json::from_str(content)?;
+    let map = value
+        .as_object_mut()
+        .ok_or_else(|| Error::InvalidInput("Settings must be a JSON object".into()))?;
+
+    let mut version = match map.get("version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| Error::InvalidInput(format!("Invalid settings version: {}", v)))?,
+    };
+    while let Some(migrate) = MIGRATIONS.get(version as usize) {
+        migrate(map);
+        version += 1;
+        map.insert("version".into(), version.into());
+    }
+
+    Ok(serde_json::from_value(value)?)
+}
+
+const SETTINGS_FILE: &str = "settings.json";
+
+pub fn get_settings_path() -> Result<PathBuf> {
+    Ok(paths::config_dir()?.join(SETTINGS_FILE))
+}
+
+/// Settings as read at startup.
+#[derive(Debug, Clone)]
+pub struct LoadedSettings {
+    pub settings: Settings,
+    /// Where an unparseable settings file was moved before the settings
+    /// were reset to the defaults.
+    pub backup: Option<PathBuf>,
+}
+
+/// Reads the settings, or the defaults if there is no settings file yet.
+/// The file is left alone if it cannot be parsed.
+pub fn read_settings() -> Result<Settings> {
+    read_settings_file(&get_settings_path()?)
+}
+
+/// Reads the settings like [`read_settings`], but changes nothing on disk:
+/// the config directory is not created and legacy files are not moved (see
+/// [`paths::peek_config_file`]).
+pub fn peek_settings() -> Result<Settings> {
+    read_settings_file(&paths::peek_config_file(SETTINGS_FILE)?)
+}
+
+fn read_settings_file(settings_path: &Path) -> Result<Settings> {
+    if !settings_path.exists() {
+        return Ok(Settings::default());
+    }
+    let content = fs::read_to_string(settings_path).map_err(|e| Error::io("read", settings_path, e))?;
+    parse_settings(&content).map_err(|e| {
+        Error::InvalidInput(format!("Settings file {} could not be read: {}", settings_path.display(), e.describe()))
+    })
+}
+
+/// Reads the settings at startup. An unparseable file is moved aside to
+/// `settings.json.<timestamp>.bak` and the defaults are used instead.
+pub fn load_settings() -> Result<LoadedSettings> {
+    load_settings_file(&get_settings_path()?)
+}
+
+fn load_settings_file(settings_path: &Path) -> Result<LoadedSettings> {
+    match read_settings_file(settings_path) {
+        Ok(settings) => Ok(LoadedSettings { settings, backup: None }),
+        Err(Error::InvalidInput(_)) => Ok(LoadedSettings {
+            settings: Settings::default(),
+            backup: Some(back_up(settings_path)?),
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+fn back_up(path: &Path) -> Result<PathBuf> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let backup = path.with_extension(format!("json.{}.bak", stamp));
+    fs::rename(path, &backup).map_err(|e| Error::io("back up", path, e))?;
+    Ok(backup)
+}
+
+/// Checks settings before they are saved: scan roots must be directories,
+/// and none may contain another. Home roots must be directories other than
+/// the current user's home.
+///
+/// Only roots that are not in `previous` must exist; a configured root that
+/// has since gone away does not block other changes and is returned as a
+/// warning instead.
+pub fn validate_settings(settings: &Settings, previous: &Settings) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+    let mut warnings = Vec::new();
+    let mut roots: Vec<(&str, PathBuf)> = Vec::new();
+
+    for root in &settings.scan_roots {
+        let found = match Path::new(root).canonicalize() {
+            Ok(path) if path.is_dir() => {
+                roots.push((root, path));
+                continue;
+            }
+            Ok(_) => format!("Scan root is not a directory: {}", root),
+            Err(_) => format!("Scan root does not exist: {}", root),
+        };
+        if previous.scan_roots.contains(root) {
+            warnings.push(found);
+        } else {
+            problems.push(found);
+        }
+    }
+    for (i, (root, path)) in roots.iter().enumerate() {
+        for (other, other_path) in &roots[i + 1..] {
+            if path == other_path {
+                problems.push(format!("Scan root listed twice: {}", other));
+            } else if path.starts_with(other_path) {
+                problems.push(format!("Scan root {} is inside scan root {}", root, other));
+            } else if other_path.starts_with(path) {
+                problems.push(format!("Scan root {} is inside scan root {}", other, root));
+            }
+        }
+    }
+
+    let home = dirs::home_dir().and_then(|h| h.canonicalize().ok());
+    let mut homes: Vec<PathBuf> = Vec::new();
+    for root in &settings.home_roots {
+        let found = match Path::new(root).canonicalize() {
+            Ok(path) if !path.is_dir() => format!("Home root is not a directory: {}", root),
+            Ok(path) if home.as_ref() == Some(&path) => {
+                problems.push(format!("Home root is the current user's home, which is always scanned: {}", root));
+                continue;
+            }
+            Ok(path) if homes.contains(&path) => {
+                problems.push(format!("Home root listed twice: {}", root));
+                continue;
+            }
+            Ok(path) => {
+                homes.push(path);
+                continue;
+            }
+            Err(_) => format!("Home root does not exist: {}", root),
+        };
+        if previous.home_roots.contains(root) {
+            warnings.push(found);
+        } else {
+            problems.push(found);
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(Error::InvalidInput(problems.join("; ")))
+    }
+}
+
+/// Validates and writes the settings, returning the warnings from
+/// [`validate_settings`].
+pub fn save_settings(settings: &Settings) -> Result<Vec<String>> {
+    let warnings = validate_settings(settings, &current().unwrap_or_default())?;
+    let settings_path = get_settings_path()?;
+    // Never write an older version than the file already has.
+    let settings = Settings {
+        version: settings.version.max(SETTINGS_VERSION),
+        ..settings.clone()
+    };
+    let content = serde_json::to_string_pretty(&settings)?;
+    // Held while writing, so a reload prompted by this write finds nothing new.
+    let mut current = lock_current();
+    write_atomic(&settings_path, content)?;
+    *current = Some(settings);
+    Ok(warnings)
+}
+
+/// The settings as last read or saved.
+static CURRENT: RwLock<Option<Settings>> = RwLock::new(None);
+
+fn lock_current() -> RwLockWriteGuard<'static, Option<Settings>> {
+    CURRENT.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Where [`current`] moved an unparseable settings file when it first read
+/// the settings.
+static RESET_BACKUP: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// The backup made when the settings were reset on first use, if any.
+pub fn reset_backup() -> Option<PathBuf> {
+    RESET_BACKUP.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// The current settings, read from the file on first use.
+pub fn current() -> Result<Settings> {
+    if let Some(settings) = CURRENT.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return Ok(settings.clone());
+    }
+    let mut current = lock_current();
+    if let Some(settings) = current.as_ref() {
+        return Ok(settings.clone());
+    }
+    let LoadedSettings { settings, backup } = load_settings()?;
+    if backup.is_some() {
+        *RESET_BACKUP.lock().unwrap_or_else(|e| e.into_inner()) = backup;
+    }
+    *current = Some(settings.clone());
+    Ok(settings)
+}
+
+/// Re-reads the settings file, returning the settings if they differ from
+/// the current ones. If the file cannot be parsed, the current settings are
+/// kept and the file is left for the user to fix.
+pub fn reload() -> Result<Option<Settings>> {
+    let settings = read_settings()?;
+    let mut current = lock_current();
+    if current.as_ref() == Some(&settings) {
+        return Ok(None);
+    }
+    *current = Some(settings.clone());
+    Ok(Some(settings))
+}
+
+/// A watch on the settings file. Dropping it stops the watch.
+pub struct SettingsWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Calls `on_change` from a background thread whenever the settings file is
+/// changed by something other than [`save_settings`], with the new settings
+/// or the error that kept them from being read.
+pub fn watch_settings<F>(on_change: F) -> Result<SettingsWatcher>
+where
+    F: Fn(Result<Settings>) + Send + 'static,
+{
+    let settings_path = get_settings_path()?;
+    // The directory, since saves replace the file rather than write to it.
+    let dir = paths::config_dir()?;
+    let watch_error = |source| Error::Watch {
+        path: dir.clone(),
+        source,
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if res.is_ok_and(|event| event.paths.contains(&settings_path)) {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(watch_error)?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(watch_error)?;
+
+    // Ends when the watcher, and with it the sending side, is dropped.
+    thread::spawn(move || {
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+            match reload() {
+                Ok(Some(settings)) => on_change(Ok(settings)),
+                Ok(None) => {}
+                Err(e) => on_change(Err(e)),
+            }
+        }
+    });
+
+    Ok(SettingsWatcher { _watcher: watcher })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_unversioned_settings() {
+        let settings = parse_settings(r#"{ "scan_roots": ["/a", " ", "/b", "/a"], "theme": "dark" }"#).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.scan_roots, ["/a", "/b"]);
+        assert!(settings.home_roots.is_empty());
+        assert_eq!(settings.extra.get("theme"), Some(&Value::from("dark")));
+    }
+
+    #[test]
+    fn backs_up_a_corrupt_file_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        assert!(read_settings_file(&path).is_err());
+        assert!(path.exists(), "reading must leave the file alone");
+
+        let loaded = load_settings_file(&path).unwrap();
+        assert_eq!(loaded.settings, Settings::default());
+        let backup = loaded.backup.unwrap();
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "{ not json");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn missing_file_reads_as_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let loaded = load_settings_file(&dir.path().join("settings.json")).unwrap();
+        assert_eq!(loaded.settings, Settings::default());
+        assert!(loaded.backup.is_none());
+    }
+}
@@ -0,0 +1,357 @@
+//! The installed skills, kept in memory and updated from watcher events.
+//!
+//! [`SkillSet`] holds the skills of every agent skills directory that
+//! [`scan_skills`](super::skills::scan_skills) would visit. A batch of
+//! [`SkillChange`]s re-reads only the skill directories it touches, and the
+//! merged list is compared with the previous one to report what was added,
+//! removed or modified. A change to a project's `.skillduck.toml` re-reads
+//! every skills directory of that project under the new config.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::agents::{find_project_skill_dirs, get_agent_definitions, home_roots, HomeRoot};
+use super::project_config::{ProjectConfig, ProjectConfigs, PROJECT_CONFIG_FILE};
+use super::skills::{merge_skills, scan_directory_for_skills, skill_key, ScopeFilter, Skill};
+use super::watcher::SkillChange;
+use super::{settings, Result};
+
+/// Skills that changed between two versions of the set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillsUpdate {
+    pub added: Vec<Skill>,
+    pub removed: Vec<Skill>,
+    pub modified: Vec<Skill>,
+}
+
+impl SkillsUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// One agent's skills directory and the skills last read from it.
+struct SkillsDir {
+    path: PathBuf,
+    scope: &'static str,
+    agent: String,
+    project_root: Option<String>,
+    /// Label of the home root a global directory is in.
+    home: Option<String>,
+    config: Option<ProjectConfig>,
+    skills: Vec<Skill>,
+}
+
+impl SkillsDir {
+    fn scan(&mut self) {
+        self.skills = self.read(&self.path);
+    }
+
+    /// Re-reads the skills under `dir`, a directory inside this one.
+    fn rescan(&mut self, dir: &Path) {
+        self.skills.retain(|s| !Path::new(&s.install_path).starts_with(dir));
+        let skills = self.read(dir);
+        self.skills.extend(skills);
+    }
+
+    fn read(&self, dir: &Path) -> Vec<Skill> {
+        let mut skills = scan_directory_for_skills(dir, self.scope, &self.agent, self.project_root.as_deref());
+        for skill in &mut skills {
+            skill.home = self.home.clone();
+        }
+        match &self.config {
+            Some(config) => config.filter_skills(skills),
+            None => skills,
+        }
+    }
+
+    /// Whether this is a skills directory of the project at `project`, a
+    /// canonical path as reported by the watcher.
+    fn in_project(&self, project: &Path) -> bool {
+        self.project_root
+            .as_deref()
+            .is_some_and(|root| Path::new(root) == project || Path::new(root).canonicalize().is_ok_and(|r| r == project))
+    }
+
+    /// `path`, reported by the watcher under a canonical root, as a path
+    /// inside this directory.
+    fn relocate(&self, path: &Path) -> Option<PathBuf> {
+        if path.starts_with(&self.path) {
+            return Some(path.to_path_buf());
+        }
+        let canonical = self.path.canonicalize().ok()?;
+        path.strip_prefix(canonical).ok().map(|rel| self.path.join(rel))
+    }
+}
+
+/// Agent skills directories in the order `scan_skills` visits them.
+fn skills_dirs(homes: &[HomeRoot], scan_roots: &[PathBuf]) -> Vec<SkillsDir> {
+    let mut dirs = Vec::new();
+    let mut configs = ProjectConfigs::default();
+    for agent in get_agent_definitions() {
+        for home in homes {
+            dirs.push(SkillsDir {
+                path: home.path.join(&agent.global_path),
+                scope: "global",
+                agent: agent.id.clone(),
+                project_root: None,
+                home: home.label.clone(),
+                config: None,
+                skills: Vec::new(),
+            });
+        }
+        for dir in find_project_skill_dirs(&agent, scan_roots) {
+            let config = configs.get(&dir.project_root).cloned();
+            if config.as_ref().is_some_and(|c| !c.targets(&agent.id)) {
+                continue;
+            }
+            dirs.push(SkillsDir {
+                path: dir.path,
+                scope: "project",
+                agent: agent.id.clone(),
+                project_root: Some(dir.project_root.to_string_lossy().to_string()),
+                home: None,
+                config,
+                skills: Vec::new(),
+            });
+        }
+    }
+    dirs
+}
+
+pub struct SkillSet {
+    homes: Vec<HomeRoot>,
+    scan_roots: Vec<PathBuf>,
+    dirs: Vec<SkillsDir>,
+    merged: BTreeMap<String, Skill>,
+}
+
+impl SkillSet {
+    /// Scans every agent's global directory under each of `homes` and project
+    /// directories under `scan_roots`.
+    pub fn load(homes: &[HomeRoot], scan_roots: &[PathBuf]) -> SkillSet {
+        let mut set = SkillSet {
+            homes: homes.to_vec(),
+            scan_roots: scan_roots.to_vec(),
+            dirs: skills_dirs(homes, scan_roots),
+            merged: BTreeMap::new(),
+        };
+        for dir in &mut set.dirs {
+            dir.scan();
+        }
+        set.merge();
+        set
+    }
+
+    /// Loads the skills of the current user and the configured home roots
+    /// using the configured scan roots.
+    pub fn current() -> Result<SkillSet> {
+        let settings = settings::current()?;
+        let homes = home_roots(&settings.home_roots)?;
+        let scan_roots: Vec<PathBuf> = settings.scan_roots.iter().map(PathBuf::from).collect();
+        Ok(SkillSet::load(&homes, &scan_roots))
+    }
+
+    /// The skills in `scope`, sorted by name like [`list_skills`](super::skills::list_skills).
+    pub fn skills(&self, scope: ScopeFilter) -> Vec<Skill> {
+        let mut skills: Vec<Skill> = self
+            .merged
+            .values()
+            .filter(|s| match scope {
+                ScopeFilter::All => true,
+                ScopeFilter::Global => s.scope == "global",
+                ScopeFilter::Project => s.scope == "project",
+            })
+            .cloned()
+            .collect();
+        skills.sort_by(|a, b| a.name.cmp(&b.name));
+        skills
+    }
+
+    /// Re-reads the skill directories touched by `changes`, and every skills
+    /// directory of a project whose config changed.
+    pub fn apply(&mut self, changes: &[SkillChange]) -> SkillsUpdate {
+        let mut skill_dirs: Vec<PathBuf> = Vec::new();
+        let mut projects: Vec<PathBuf> = Vec::new();
+        for change in changes {
+            let dir = PathBuf::from(&change.skill_dir);
+            let list = if Path::new(&change.path).file_name().is_some_and(|n| n == PROJECT_CONFIG_FILE) {
+                &mut projects
+            } else {
+                &mut skill_dirs
+            };
+            if !list.contains(&dir) {
+                list.push(dir);
+            }
+        }
+
+        // A skill in a skills directory that did not exist when the set was
+        // loaded, such as one in a new project. Skills anywhere else under a
+        // scan root are not listed, so they need no new directories.
+        let new_dir = skill_dirs.iter().any(|skill_dir| {
+            !self.dirs.iter().any(|d| d.relocate(skill_dir).is_some()) && in_project_skills_path(skill_dir)
+        });
+        // The config decides which agents' directories are listed and what is
+        // filtered out of them.
+        if new_dir || !projects.is_empty() {
+            self.refresh_dirs();
+        }
+        for dir in &mut self.dirs {
+            if projects.iter().any(|project| dir.in_project(project)) {
+                dir.scan();
+            }
+        }
+
+        for skill_dir in &skill_dirs {
+            for dir in &mut self.dirs {
+                if let Some(path) = dir.relocate(skill_dir) {
+                    dir.rescan(&path);
+                }
+            }
+        }
+        self.merge()
+    }
+
+    /// Replaces the set with `other`, a fresh scan, reporting the difference.
+    pub fn replace(&mut self, other: SkillSet) -> SkillsUpdate {
+        let update = diff(&self.merged, &other.merged);
+        *self = other;
+        update
+    }
+
+    /// Looks for new project skills directories, keeping what is known of the others.
+    fn refresh_dirs(&mut self) {
+        let mut known = std::mem::take(&mut self.dirs);
+        self.dirs = skills_dirs(&self.homes, &self.scan_roots)
+            .into_iter()
+            .map(|mut dir| {
+                match known.iter().position(|k| k.path == dir.path && k.agent == dir.agent) {
+                    Some(i) => dir.skills = known.swap_remove(i).skills,
+                    None => dir.scan(),
+                }
+                dir
+            })
+            .collect();
+    }
+
+    fn merge(&mut self) -> SkillsUpdate {
+        let all = self.dirs.iter().flat_map(|d| d.skills.iter().cloned()).collect();
+        let merged: BTreeMap<String, Skill> = merge_skills(all).into_iter().map(|s| (skill_key(&s), s)).collect();
+        let update = diff(&self.merged, &merged);
+        self.merged = merged;
+        update
+    }
+}
+
+/// Whether `path` lies inside some agent's project skills path, such as
+/// `<project>/.claude/skills/<skill>`.
+fn in_project_skills_path(path: &Path) -> bool {
+    let agents = get_agent_definitions();
+    path.ancestors()
+        .skip(1)
+        .any(|dir| agents.iter().any(|agent| dir.ends_with(&agent.project_path)))
+}
+
+fn diff(previous: &BTreeMap<String, Skill>, current: &BTreeMap<String, Skill>) -> SkillsUpdate {
+    let mut update = SkillsUpdate::default();
+    for (key, skill) in current {
+        match previous.get(key) {
+            None => update.added.push(skill.clone()),
+            Some(old) if old != skill => update.modified.push(skill.clone()),
+            Some(_) => {}
+        }
+    }
+    for (key, skill) in previous {
+        if !current.contains_key(key) {
+            update.removed.push(skill.clone());
+        }
+    }
+    update
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::watcher::ChangeKind;
+    use std::fs;
+
+    fn write_skill(dir: &Path, name: &str, description: &str) {
+        fs::create_dir_all(dir).unwrap();
+        let content = format!("---\nname: {}\ndescription: {}\n---\nBody\n", name, description);
+        fs::write(dir.join("SKILL.md"), content).unwrap();
+    }
+
+    fn change(kind: ChangeKind, skill_dir: &Path) -> SkillChange {
+        SkillChange {
+            kind,
+            skill_dir: skill_dir.to_string_lossy().to_string(),
+            path: skill_dir.join("SKILL.md").to_string_lossy().to_string(),
+            from: None,
+        }
+    }
+
+    fn names(skills: &[Skill]) -> Vec<&str> {
+        skills.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn apply_reports_added_removed_and_modified_skills() {
+        let home = tempfile::tempdir().unwrap();
+        let skills_dir = home.path().join(".claude/skills");
+        write_skill(&skills_dir.join("alpha"), "alpha", "First");
+        write_skill(&skills_dir.join("beta"), "beta", "Second");
+        let homes = [HomeRoot {
+            path: home.path().to_path_buf(),
+            label: None,
+        }];
+        let mut set = SkillSet::load(&homes, &[]);
+        assert_eq!(names(&set.skills(ScopeFilter::All)), ["alpha", "beta"]);
+
+        write_skill(&skills_dir.join("alpha"), "alpha", "First, edited");
+        fs::remove_dir_all(skills_dir.join("beta")).unwrap();
+        write_skill(&skills_dir.join("gamma"), "gamma", "Third");
+        let update = set.apply(&[
+            change(ChangeKind::Modified, &skills_dir.join("alpha")),
+            change(ChangeKind::Removed, &skills_dir.join("beta")),
+            change(ChangeKind::Created, &skills_dir.join("gamma")),
+        ]);
+
+        assert_eq!(names(&update.added), ["gamma"]);
+        assert_eq!(names(&update.removed), ["beta"]);
+        assert_eq!(names(&update.modified), ["alpha"]);
+        assert_eq!(update.modified[0].description, "First, edited");
+        assert_eq!(names(&set.skills(ScopeFilter::All)), ["alpha", "gamma"]);
+
+        assert!(set.apply(&[change(ChangeKind::Modified, &skills_dir.join("alpha"))]).is_empty());
+    }
+
+    #[test]
+    fn apply_refilters_a_project_when_its_config_changes() {
+        let scan_root = tempfile::tempdir().unwrap();
+        let project = scan_root.path().canonicalize().unwrap().join("app");
+        let skills_dir = project.join(".claude/skills");
+        write_skill(&skills_dir.join("alpha"), "alpha", "First");
+        write_skill(&skills_dir.join("draft"), "draft", "Unfinished");
+        let config = project.join(PROJECT_CONFIG_FILE);
+        fs::write(&config, "ignore = []\n").unwrap();
+        let mut set = SkillSet::load(&[], &[scan_root.path().to_path_buf()]);
+        assert_eq!(names(&set.skills(ScopeFilter::Project)), ["alpha", "draft"]);
+
+        let config_change = |kind| SkillChange {
+            kind,
+            skill_dir: project.to_string_lossy().to_string(),
+            path: config.to_string_lossy().to_string(),
+            from: None,
+        };
+        fs::write(&config, "ignore = [\".claude/skills/draft/\"]\n").unwrap();
+        let update = set.apply(&[config_change(ChangeKind::Modified)]);
+        assert!(update.added.is_empty() && update.modified.is_empty());
+        assert_eq!(names(&update.removed), ["draft"]);
+        assert_eq!(names(&set.skills(ScopeFilter::Project)), ["alpha"]);
+
+        fs::remove_file(&config).unwrap();
+        let update = set.apply(&[config_change(ChangeKind::Removed)]);
+        assert_eq!(names(&update.added), ["draft"]);
+    }
+}
@@ -0,0 +1,529 @@
+//! Declarative test cases for skill scripts.
+//!
+//! A skill declares its tests in YAML files directly under `tests/`
+//! (`tests/*.yaml` or `tests/*.yml`). Each file holds one case or a list of
+//! cases:
+//!
+//! ```yaml
+//! - name: greets by name
+//!   script: scripts/greet.sh
+//!   args: [world]
+//!   expect:
+//!     exit_code: 0
+//!     stdout: Hello, world
+//!     files:
+//!       - path: greeting.txt
+//!         matches: "^Hello"
+//! ```
+//!
+//! Every case runs in its own sandbox from the skill directory and takes the
+//! options of [`RunOptions`] as keys of its own. Unknown keys are refused, so
+//! a misspelled `expect` fails the file instead of passing unchecked. Output
+//! and file contents are compared without trailing newlines. A skill without
+//! any test case is an error rather than a pass.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::sandbox::limits::ResourceLimits;
+use super::sandbox::{self, join_inside, Interpreter, RunOptions, SandboxOptions, ScriptOutput};
+use super::{Error, Result};
+
+const TESTS_DIR: &str = "tests";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Script path relative to the skill directory.
+    pub script: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub stdin: Option<String>,
+    #[serde(default)]
+    pub interpreter: Option<Interpreter>,
+    #[serde(default)]
+    pub allow_network: bool,
+    #[serde(default)]
+    pub limits: ResourceLimits,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+impl TestCase {
+    fn run_options(&self) -> RunOptions {
+        RunOptions {
+            args: self.args.clone(),
+            env: self.env.clone(),
+            stdin: self.stdin.clone(),
+            interpreter: self.interpreter,
+            allow_network: self.allow_network,
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Expectations {
+    pub exit_code: i32,
+    /// Exact stdout.
+    pub stdout: Option<String>,
+    /// Regular expressions stdout must match.
+    pub stdout_matches: Vec<String>,
+    /// Exact stderr.
+    pub stderr: Option<String>,
+    /// Regular expressions stderr must match.
+    pub stderr_matches: Vec<String>,
+    pub files: Vec<ExpectedFile>,
+}
+
+/// A file the script should (or should not) leave behind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedFile {
+    /// Path relative to the skill directory.
+    pub path: String,
+    #[serde(default = "default_exists")]
+    pub exists: bool,
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Regular expression the content must match.
+    #[serde(default)]
+    pub matches: Option<String>,
+}
+
+fn default_exists() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestFailure {
+    pub message: String,
+    /// Unified diff of expected and actual text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCaseResult {
+    pub name: String,
+    /// Test file the case comes from, relative to the skill directory.
+    pub file: String,
+    pub passed: bool,
+    pub duration_ms: u64,
+    pub failures: Vec<TestFailure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestReport {
+    pub skill: String,
+    pub path: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+    pub cases: Vec<TestCaseResult>,
+}
+
+fn skill_dir(path: &str) -> PathBuf {
+    let p = Path::new(path);
+    if p.is_file() {
+        p.parent().unwrap_or(p).to_path_buf()
+    } else {
+        p.to_path_buf()
+    }
+}
+
+struct TestFile {
+    /// Path relative to the skill directory.
+    path: String,
+    /// The cases, or why the file could not be read; reported as a failed case.
+    cases: std::result::Result<Vec<TestCase>, String>,
+}
+
+/// Reads every test file of the skill at `skill_dir`.
+fn load_test_files(skill_dir: &Path) -> Result<Vec<TestFile>> {
+    let dir = skill_dir.join(TESTS_DIR);
+    if !dir.is_dir() {
+        return Err(Error::NotFound(dir));
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| Error::io("read", &dir, e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+        .collect();
+    paths.sort();
+
+    let mut files = Vec::new();
+    for path in paths {
+        let rel = format!("{}/{}", TESTS_DIR, path.file_name().unwrap_or_default().to_string_lossy());
+        let cases = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_cases(&content).map_err(|e| e.describe()));
+        files.push(TestFile { path: rel, cases });
+    }
+    Ok(files)
+}
+
+fn parse_cases(content: &str) -> Result<Vec<TestCase>> {
+    let value: serde_yaml::Value = serde_yaml::from_str(content)?;
+    Ok(match value {
+        serde_yaml::Value::Null => Vec::new(),
+        serde_yaml::Value::Sequence(_) => serde_yaml::from_value(value)?,
+        _ => vec![serde_yaml::from_value(value)?],
+    })
+}
+
+/// Runs every test case of the skill at `path` (a skill directory or its
+/// SKILL.md).
+pub async fn run_skill_tests(path: &str) -> Result<TestReport> {
+    let dir = skill_dir(path);
+    if !dir.join("SKILL.md").is_file() {
+        return Err(Error::NotFound(dir.join("SKILL.md")));
+    }
+
+    let started = Instant::now();
+    let mut cases = Vec::new();
+    for TestFile { path: file, cases: parsed } in load_test_files(&dir)? {
+        let stem = Path::new(&file).file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(message) => {
+                cases.push(TestCaseResult {
+                    name: stem,
+                    file,
+                    passed: false,
+                    duration_ms: 0,
+                    failures: vec![TestFailure {
+                        message: format!("Invalid test file: {}", message),
+                        diff: None,
+                    }],
+                    exit_code: None,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                });
+                continue;
+            }
+        };
+
+        let count = parsed.len();
+        for (index, case) in parsed.into_iter().enumerate() {
+            let name = case.name.clone().unwrap_or_else(|| match count {
+                1 => stem.clone(),
+                _ => format!("{} #{}", stem, index + 1),
+            });
+            cases.push(run_case(&dir, &file, name, &case).await);
+        }
+    }
+
+    if cases.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "No test cases in {}",
+            dir.join(TESTS_DIR).display()
+        )));
+    }
+
+    let passed = cases.iter().filter(|c| c.passed).count();
+    Ok(TestReport {
+        skill: dir.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        path: dir.to_string_lossy().to_string(),
+        passed,
+        failed: cases.len() - passed,
+        duration_ms: started.elapsed().as_millis() as u64,
+        cases,
+    })
+}
+
+async fn run_case(skill_dir: &Path, file: &str, name: String, case: &TestCase) -> TestCaseResult {
+    let started = Instant::now();
+    let mut result = TestCaseResult {
+        name,
+        file: file.to_string(),
+        passed: false,
+        duration_ms: 0,
+        failures: Vec::new(),
+        exit_code: None,
+        stdout: String::new(),
+        stderr: String::new(),
+    };
+
+    match sandbox::create_sandbox(&skill_dir.to_string_lossy(), &SandboxOptions::default()) {
+        Ok(info) => {
+            match sandbox::run_sandbox_script(&info.path, &case.script, &case.run_options()).await {
+                Ok(output) => {
+                    result.failures = check(&case.expect, &output, Path::new(&info.path));
+                    result.exit_code = Some(output.exit_code);
+                    result.stdout = output.stdout;
+                    result.stderr = output.stderr;
+                }
+                Err(e) => result.failures.push(TestFailure {
+                    message: e.describe(),
+                    diff: None,
+                }),
+            }
+            let _ = sandbox::cleanup_sandbox(&info.temp_dir_handle);
+        }
+        Err(e) => result.failures.push(TestFailure {
+            message: format!("Could not create sandbox: {}", e.describe()),
+            diff: None,
+        }),
+    }
+
+    result.passed = result.failures.is_empty();
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+fn check(expect: &Expectations, output: &ScriptOutput, cwd: &Path) -> Vec<TestFailure> {
+    let mut failures = Vec::new();
+
+    if let Some(limit) = output.limit_hit {
+        failures.push(TestFailure {
+            message: format!("Stopped after exceeding the {} limit", limit.label()),
+            diff: None,
+        });
+    }
+    if output.exit_code != expect.exit_code {
+        failures.push(TestFailure {
+            message: format!("Expected exit code {}, got {}", expect.exit_code, output.exit_code),
+            diff: None,
+        });
+    }
+
+    check_text("stdout", expect.stdout.as_deref(), &expect.stdout_matches, &output.stdout, &mut failures);
+    check_text("stderr", expect.stderr.as_deref(), &expect.stderr_matches, &output.stderr, &mut failures);
+
+    for file in &expect.files {
+        let path = match join_inside(cwd, &file.path) {
+            Ok(path) => path,
+            Err(e) => {
+                failures.push(TestFailure {
+                    message: e.to_string(),
+                    diff: None,
+                });
+                continue;
+            }
+        };
+        match (file.exists, path.is_file()) {
+            (true, false) => failures.push(TestFailure {
+                message: format!("Expected {} to exist", file.path),
+                diff: None,
+            }),
+            (false, true) => failures.push(TestFailure {
+                message: format!("Expected {} not to exist", file.path),
+                diff: None,
+            }),
+            (true, true) => {
+                let content = fs::read(&path).map(|b| String::from_utf8_lossy(&b).to_string()).unwrap_or_default();
+                let matches: Vec<String> = file.matches.iter().cloned().collect();
+                check_text(&file.path, file.content.as_deref(), &matches, &content, &mut failures);
+            }
+            (false, false) => {}
+        }
+    }
+
+    failures
+}
+
+fn check_text(label: &str, exact: Option<&str>, patterns: &[String], actual: &str, failures: &mut Vec<TestFailure>) {
+    if let Some(expected) = exact {
+        let (expected, actual) = (expected.trim_end_matches('\n'), actual.trim_end_matches('\n'));
+        if expected != actual {
+            failures.push(TestFailure {
+                message: format!("{} differs from the expected text", label),
+                diff: Some(text_diff(expected, actual)),
+            });
+        }
+    }
+
+    for pattern in patterns {
+        match Regex::new(pattern) {
+            Ok(re) if re.is_match(actual) => {}
+            Ok(_) => failures.push(TestFailure {
+                message: format!("{} does not match /{}/", label, pattern),
+                diff: None,
+            }),
+            Err(e) => failures.push(TestFailure {
+                message: format!("Invalid pattern /{}/: {}", pattern, e),
+                diff: None,
+            }),
+        }
+    }
+}
+
+fn text_diff(expected: &str, actual: &str) -> String {
+    let (expected, actual) = (format!("{}\n", expected), format!("{}\n", actual));
+    TextDiff::from_lines(&expected, &actual)
+        .unified_diff()
+        .header("expected", "actual")
+        .to_string()
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0.
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders test reports as a JUnit XML document, one `<testsuite>` per skill.
+pub fn junit_xml(reports: &[TestReport]) -> String {
+    let tests: usize = reports.iter().map(|r| r.cases.len()).sum();
+    let failures: usize = reports.iter().map(|r| r.failed).sum();
+    let seconds = |ms: u64| ms as f64 / 1000.0;
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, "<testsuites tests=\"{}\" failures=\"{}\">", tests, failures);
+    for report in reports {
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+            xml_escape(&report.skill),
+            report.cases.len(),
+            report.failed,
+            seconds(report.duration_ms)
+        );
+        for case in &report.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" time=\"{:.3}\"",
+                xml_escape(&case.name),
+                xml_escape(&report.skill),
+                xml_escape(&case.file),
+                seconds(case.duration_ms)
+            );
+            if case.passed {
+                xml.push_str("/>\n");
+                continue;
+            }
+            xml.push_str(">\n");
+            for failure in &case.failures {
+                let mut body = failure.message.clone();
+                if let Some(diff) = &failure.diff {
+                    body.push('\n');
+                    body.push_str(diff);
+                }
+                let _ = writeln!(
+                    xml,
+                    "      <failure message=\"{}\">{}</failure>",
+                    xml_escape(&failure.message),
+                    xml_escape(&body)
+                );
+            }
+            let _ = writeln!(xml, "      <system-out>{}</system-out>", xml_escape(&case.stdout));
+            let _ = writeln!(xml, "      <system-err>{}</system-err>", xml_escape(&case.stderr));
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(exit_code: i32, stdout: &str) -> ScriptOutput {
+        ScriptOutput {
+            stdout: stdout.into(),
+            stderr: String::new(),
+            exit_code,
+            duration_ms: 0,
+            isolation: Default::default(),
+            limit_hit: None,
+            killed: false,
+            changes: Default::default(),
+        }
+    }
+
+    fn expect(yaml: &str) -> Expectations {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn messages(failures: &[TestFailure]) -> Vec<&str> {
+        failures.iter().map(|f| f.message.as_str()).collect()
+    }
+
+    #[test]
+    fn passes_when_every_expectation_holds() {
+        let cwd = tempfile::tempdir().unwrap();
+        fs::write(cwd.path().join("out.txt"), "total: 3\n").unwrap();
+        let expect = expect(
+            r#"
+            stdout: "done\n"
+            stdout_matches: ["^do"]
+            files:
+              - { path: out.txt, matches: 'total: \d+' }
+              - { path: tmp.txt, exists: false }
+            "#,
+        );
+        assert!(check(&expect, &output(0, "done"), cwd.path()).is_empty());
+    }
+
+    #[test]
+    fn reports_each_unmet_expectation() {
+        let cwd = tempfile::tempdir().unwrap();
+        let expect = expect(
+            r#"
+            exit_code: 0
+            stdout: done
+            stderr_matches: [warning]
+            files: [{ path: out.txt }, { path: ../escape.txt }]
+            "#,
+        );
+        let failures = check(&expect, &output(2, "failed"), cwd.path());
+
+        assert_eq!(
+            messages(&failures),
+            [
+                "Expected exit code 0, got 2",
+                "stdout differs from the expected text",
+                "stderr does not match /warning/",
+                "Expected out.txt to exist",
+                "Path must stay inside the sandbox: ../escape.txt",
+            ]
+        );
+        assert!(failures[1].diff.as_deref().is_some_and(|d| d.contains("-done") && d.contains("+failed")));
+    }
+
+    #[test]
+    fn rejects_unknown_expectations() {
+        assert!(serde_yaml::from_str::<Expectations>("stdout_contains: x").is_err());
+    }
+
+    #[test]
+    fn rejects_misspelled_limits() {
+        let case = "script: run.sh\nlimits:\n  wall_time_ms: 500\n";
+        assert_eq!(parse_cases(case).unwrap()[0].limits.wall_time_ms, 500);
+
+        let typo = "script: run.sh\nlimits:\n  wall_time_sec: 5\n";
+        assert!(parse_cases(typo).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::agents::{find_agent, find_project_skill_dirs, get_agent_definitions, home_roots, AgentDefinition, HomeRoot};
+use super::project_config::ProjectConfigs;
+use super::{settings, Error, Result};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Skill {
+    pub name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<String>,
+    pub install_path: String,
+    pub scope: String,
+    pub agents: Vec<String>,
+    pub has_update: bool,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+    /// The home root a global skill was found in, when it is not the current
+    /// user's home.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home: Option<String>,
+}
+
+#[cfg(test)]
+impl Skill {
+    /// A global `claude-code` skill with nothing but a name and a path.
+    pub(crate) fn for_test(name: &str, install_path: &str) -> Skill {
+        Skill {
+            name: name.into(),
+            description: String::new(),
+            license: None,
+            compatibility: None,
+            metadata: None,
+            allowed_tools: None,
+            install_path: install_path.into(),
+            scope: "global".into(),
+            agents: vec!["claude-code".into()],
+            has_update: false,
+            body: String::new(),
+            project_root: None,
+            home: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SkillFrontmatter {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub compatibility: Option<String>,
+    pub metadata: Option<HashMap<String, serde_yaml::Value>>,
+    #[serde(rename = "allowed-tools")]
+    pub allowed_tools: Option<String>,
+}
+
+/// Which installed skills to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeFilter {
+    All,
+    Global,
+    Project,
+}
+
+impl ScopeFilter {
+    pub fn parse(scope: &str) -> Result<Self> {
+        match scope {
+            "all" => Ok(ScopeFilter::All),
+            "global" => Ok(ScopeFilter::Global),
+            "project" => Ok(ScopeFilter::Project),
+            other => Err(Error::InvalidInput(format!("Invalid scope: {}", other))),
+        }
+    }
+
+    fn includes_global(self) -> bool {
+        matches!(self, ScopeFilter::All | ScopeFilter::Global)
+    }
+
+    fn includes_project(self) -> bool {
+        matches!(self, ScopeFilter::All | ScopeFilter::Project)
+    }
+}
+
+/// Splits a SKILL.md into its raw YAML frontmatter and body.
+pub fn split_frontmatter(content: &str) -> Result<(&str, &str)> {
+    let trimmed = content.trim_start();
+    if !trimmed.starts_with("---") {
+        return Err(Error::MissingFrontmatter);
+    }
+
+    let after_first = &trimmed[3..];
+    let end_idx = after_first.find("\n---").ok_or(Error::UnclosedFrontmatter)?;
+    let yaml_str = &after_first[..end_idx];
+    let body_start = end_idx + 4; // skip \n---
+    let body = if body_start < after_first.len() {
+        after_first[body_start..].trim_start_matches('\n')
+    } else {
+        ""
+    };
+
+    Ok((yaml_str, body))
+}
+
+pub fn parse_skill_md_content(content: &str) -> Result<(SkillFrontmatter, String)> {
+    let (yaml_str, body) = split_frontmatter(content)?;
+    let frontmatter: SkillFrontmatter = serde_yaml::from_str(yaml_str)?;
+    Ok((frontmatter, body.to_string()))
+}
+
+fn flatten_metadata(metadata: Option<HashMap<String, serde_yaml::Value>>) -> Option<HashMap<String, String>> {
+    metadata.map(|m| {
+        m.into_iter()
+            .map(|(k, v)| {
+                let val = match v {
+                    serde_yaml::Value::String(s) => s,
+                    serde_yaml::Value::Bool(b) => b.to_string(),
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    _ => format!("{:?}", v),
+                };
+                (k, val)
+            })
+            .collect()
+    })
+}
+
+fn build_skill(fm: SkillFrontmatter, body: String, name: String, install_path: String, scope: &str) -> Skill {
+    Skill {
+        name,
+        description: fm.description.unwrap_or_default(),
+        license: fm.license,
+        compatibility: fm.compatibility,
+        metadata: flatten_metadata(fm.metadata),
+        allowed_tools: fm.allowed_tools,
+        install_path,
+        scope: scope.to_string(),
+        agents: vec![],
+        has_update: false,
+        body,
+        project_root: None,
+        home: None,
+    }
+}
+
+pub fn scan_directory_for_skills(base_path: &Path, scope: &str, agent_id: &str, project_root: Option<&str>) -> Vec<Skill> {
+    let mut skills = Vec::new();
+
+    if !base_path.exists() {
+        return skills;
+    }
+
+    for entry in WalkDir::new(base_path).follow_links(true).max_depth(4).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_name() == "SKILL.md" {
+            if let Ok(content) = fs::read_to_string(entry.path()) {
+                if let Ok((mut fm, body)) = parse_skill_md_content(&content) {
+                    let name = fm.name.take().unwrap_or_else(|| {
+                        entry
+                            .path()
+                            .parent()
+                            .and_then(|p| p.file_name())
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    });
+
+                    let mut skill = build_skill(fm, body, name, entry.path().to_string_lossy().to_string(), scope);
+                    skill.agents = vec![agent_id.to_string()];
+                    skill.project_root = project_root.map(|s| s.to_string());
+                    skills.push(skill);
+                }
+            }
+        }
+    }
+
+    skills
+}
+
+/// Identifies a skill across agents by its scope and name, and its home root
+/// if it comes from another home.
+pub fn skill_key(skill: &Skill) -> String {
+    match &skill.home {
+        Some(home) => format!("{}::{}::{}", skill.scope, skill.name, home),
+        None => format!("{}::{}", skill.scope, skill.name),
+    }
+}
+
+pub fn merge_skills(all_skills: Vec<Skill>) -> Vec<Skill> {
+    let mut map: HashMap<String, Skill> = HashMap::new();
+
+    for skill in all_skills {
+        let key = skill_key(&skill);
+        if let Some(existing) = map.get_mut(&key) {
+            for agent in &skill.agents {
+                if !existing.agents.contains(agent) {
+                    existing.agents.push(agent.clone());
+                }
+            }
+        } else {
+            map.insert(key, skill);
+        }
+    }
+
+    let mut result: Vec<Skill> = map.into_values().collect();
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    result
+}
+
+/// Scans every agent's global directory under each of `homes` and project
+/// directories under `scan_roots`, merging skills installed for several
+/// agents. A project's `.skillduck.toml` limits which agents and paths are
+/// scanned.
+pub fn scan_skills(scope: ScopeFilter, homes: &[HomeRoot], scan_roots: &[PathBuf]) -> Vec<Skill> {
+    let agents = get_agent_definitions();
+    let mut all_skills = Vec::new();
+    let mut configs = ProjectConfigs::default();
+
+    for agent in &agents {
+        if scope.includes_global() {
+            for home in homes {
+                let global_path = home.path.join(&agent.global_path);
+                for mut skill in scan_directory_for_skills(&global_path, "global", &agent.id, None) {
+                    skill.home = home.label.clone();
+                    all_skills.push(skill);
+                }
+            }
+        }
+
+        if scope.includes_project() {
+            for dir in find_project_skill_dirs(agent, scan_roots) {
+                let config = configs.get(&dir.project_root);
+                if config.is_some_and(|c| !c.targets(&agent.id)) {
+                    continue;
+                }
+                let project_root = dir.project_root.to_string_lossy().to_string();
+                let skills = scan_directory_for_skills(&dir.path, "project", &agent.id, Some(&project_root));
+                all_skills.extend(match config {
+                    Some(config) => config.filter_skills(skills),
+                    None => skills,
+                });
+            }
+        }
+    }
+
+    merge_skills(all_skills)
+}
+
+/// Lists installed skills for the current user and `home_roots` (by
+/// default, those configured) using the configured scan roots.
+pub fn list_skills(scope: ScopeFilter, home_roots: Option<Vec<String>>) -> Result<Vec<Skill>> {
+    let settings = settings::current()?;
+    let homes = self::home_roots(&home_roots.unwrap_or(settings.home_roots))?;
+    let scan_roots: Vec<PathBuf> = settings.scan_roots.iter().map(PathBuf::from).collect();
+
+    Ok(scan_skills(scope, &homes, &scan_roots))
+}
+
+/// Skills the agent would load: its global skills plus, for a project, the
+/// project's skills. A project skill shadows a global one with the same name.
+///
+/// Only the current user's home is read, not the configured home roots: an
+/// agent run here loads the global skills of the user running it, and those
+/// of a dev container or CI user never reach its context.
+pub fn agent_skills(agent_id: &str, project_path: Option<&str>) -> Result<Vec<Skill>> {
+    let agent = find_agent(agent_id)?;
+    let home = dirs::home_dir().ok_or(Error::HomeDirUnavailable)?;
+
+    let mut skills = Vec::new();
+    if let Some(project) = project_path {
+        let dir = Path::new(project).join(&agent.project_path);
+        skills.extend(scan_directory_for_skills(&dir, "project", &agent.id, Some(project)));
+    }
+    for skill in scan_directory_for_skills(&home.join(&agent.global_path), "global", &agent.id, None) {
+        if !skills.iter().any(|s: &Skill| s.name == skill.name) {
+            skills.push(skill);
+        }
+    }
+    skills.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(skills)
+}
+
+pub fn parse_skill_md(path: &str) -> Result<Skill> {
+    let content = fs::read_to_string(path).map_err(|e| Error::io("read", path, e))?;
+    let (mut fm, body) = parse_skill_md_content(&content)?;
+    let name = fm.name.take().unwrap_or_default();
+
+    Ok(build_skill(fm, body, name, path.to_string(), "unknown"))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HomeSkillsDirectories {
+    pub home: String,
+    pub global: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillsDirectories {
+    pub project: Vec<String>,
+    pub global: Vec<String>,
+    /// Global skill directories in each configured home root.
+    pub other_homes: Vec<HomeSkillsDirectories>,
+}
+
+fn existing_global_dirs(agents: &[AgentDefinition], home: &Path) -> Vec<String> {
+    agents
+        .iter()
+        .map(|agent| home.join(&agent.global_path))
+        .filter(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Returns existing global skill directories, in the current user's home and
+/// the configured home roots, and project directories under `cwd`.
+pub fn get_skills_directories(cwd: &Path) -> Result<SkillsDirectories> {
+    let agents = get_agent_definitions();
+    let homes = home_roots(&settings::current()?.home_roots)?;
+
+    let project = agents
+        .iter()
+        .map(|agent| cwd.join(&agent.project_path))
+        .filter(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    Ok(SkillsDirectories {
+        project,
+        global: existing_global_dirs(&agents, &homes[0].path),
+        other_homes: homes[1..]
+            .iter()
+            .map(|home| HomeSkillsDirectories {
+                home: home.label.clone().unwrap_or_default(),
+                global: existing_global_dirs(&agents, &home.path),
+            })
+            .collect(),
+    })
+}
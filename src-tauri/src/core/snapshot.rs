@@ -0,0 +1,294 @@
+//! Snapshots of an agent's skills directory.
+//!
+//! A snapshot is a gzipped tarball of one agent's skills directory (global or
+//! for a single project) stored under `snapshots/` in the data directory, with a JSON
+//! sidecar recording a content hash per skill so snapshots can be compared
+//! with the current state without unpacking them.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+use super::agents::find_agent;
+use super::fs::{write_atomic, write_atomic_with};
+use super::sync::{find_skill_dirs, skills_base_dir};
+use super::{paths, Error, Result};
+
+/// Name of the top-level directory inside every snapshot archive.
+const ARCHIVE_ROOT: &str = "skills";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub agent: String,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    /// The skills directory the snapshot was taken from and restores to.
+    pub skills_dir: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Unix time in milliseconds.
+    pub created_at: u64,
+    pub size_bytes: u64,
+    /// Skill directory (relative to `skills_dir`) to content hash.
+    pub skills: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// Skills installed now that the snapshot does not have.
+    pub added: Vec<String>,
+    /// Skills in the snapshot that are no longer installed.
+    pub removed: Vec<String>,
+    /// Skills whose files differ from the snapshot.
+    pub modified: Vec<String>,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotStatus {
+    #[serde(flatten)]
+    pub info: SnapshotInfo,
+    /// `None` when the installed skills could not be read to compare.
+    pub diff: Option<SnapshotDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn snapshots_dir() -> Result<PathBuf> {
+    paths::data_subdir("snapshots")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Hashes every file under `dir` (paths and contents, in a stable order).
+pub fn hash_dir(dir: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut files: Vec<PathBuf> = WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+
+    for file in files {
+        let rel = file.strip_prefix(dir).unwrap_or(&file);
+        hasher.update(rel.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        let content = fs::read(&file).map_err(|e| Error::io("read", &file, e))?;
+        hasher.update(&content);
+        hasher.update([0]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hash_skills(base: &Path) -> Result<BTreeMap<String, String>> {
+    let mut skills = BTreeMap::new();
+    for rel in find_skill_dirs(base) {
+        skills.insert(rel.to_string_lossy().to_string(), hash_dir(&base.join(&rel))?);
+    }
+    Ok(skills)
+}
+
+fn diff_skills(snapshot: &BTreeMap<String, String>, current: &BTreeMap<String, String>) -> SnapshotDiff {
+    let mut diff = SnapshotDiff::default();
+    for (name, hash) in current {
+        match snapshot.get(name) {
+            None => diff.added.push(name.clone()),
+            Some(old) if old != hash => diff.modified.push(name.clone()),
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    diff.removed = snapshot
+        .keys()
+        .filter(|name| !current.contains_key(*name))
+        .cloned()
+        .collect();
+    diff
+}
+
+fn validate_id(id: &str) -> Result<()> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(Error::InvalidInput(format!("Invalid snapshot id: {}", id)));
+    }
+    Ok(())
+}
+
+fn archive_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.tar.gz", id))
+}
+
+fn meta_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn read_info(dir: &Path, id: &str) -> Result<SnapshotInfo> {
+    validate_id(id)?;
+    let meta = meta_path(dir, id);
+    if !meta.exists() {
+        return Err(Error::NotFound(meta));
+    }
+    let content = fs::read_to_string(&meta).map_err(|e| Error::io("read", &meta, e))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Archives every skill of `agent` in the given scope.
+pub fn create_snapshot(
+    agent_id: &str,
+    global: bool,
+    project_path: Option<&str>,
+    label: Option<String>,
+) -> Result<SnapshotInfo> {
+    let agent = find_agent(agent_id)?;
+    let base = skills_base_dir(&agent, global, project_path)?;
+    if !base.is_dir() {
+        return Err(Error::NotFound(base));
+    }
+
+    let dir = snapshots_dir()?;
+    let created_at = now_ms();
+    let id = format!("{}-{}", created_at, agent.id);
+    let archive = archive_path(&dir, &id);
+
+    write_atomic_with(&archive, |file| {
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        // Archive symlinks as links; following them could pull in files from
+        // anywhere on disk.
+        builder.follow_symlinks(false);
+        builder
+            .append_dir_all(ARCHIVE_ROOT, &base)
+            .map_err(|e| Error::io("archive", &base, e))?;
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(|e| Error::io("write", &archive, e))?;
+        Ok(())
+    })?;
+
+    let size_bytes = fs::metadata(&archive).map(|m| m.len()).unwrap_or_default();
+    let info = SnapshotInfo {
+        id: id.clone(),
+        agent: agent.id.clone(),
+        scope: if global { "global" } else { "project" }.to_string(),
+        project_path: if global { None } else { project_path.map(|p| p.to_string()) },
+        skills_dir: base.to_string_lossy().to_string(),
+        label,
+        created_at,
+        size_bytes,
+        skills: hash_skills(&base)?,
+    };
+
+    let meta = meta_path(&dir, &id);
+    write_atomic(&meta, serde_json::to_string_pretty(&info)?)?;
+
+    Ok(info)
+}
+
+/// Lists snapshots (optionally for one agent), newest first, each with a diff
+/// against what is installed now.
+pub fn list_snapshots(agent_id: Option<&str>) -> Result<Vec<SnapshotStatus>> {
+    let dir = snapshots_dir()?;
+    let mut snapshots = Vec::new();
+
+    for entry in fs::read_dir(&dir).map_err(|e| Error::io("read", &dir, e))?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(id) = name.strip_suffix(".json") else {
+            continue;
+        };
+        let Ok(info) = read_info(&dir, id) else {
+            continue;
+        };
+        if agent_id.is_some_and(|a| a != info.agent) {
+            continue;
+        }
+
+        // One unreadable skill leaves this snapshot's status unknown rather
+        // than failing the whole listing.
+        let (diff, error) = match hash_skills(Path::new(&info.skills_dir)) {
+            Ok(current) => (Some(diff_skills(&info.skills, &current)), None),
+            Err(e) => (None, Some(e.describe())),
+        };
+        snapshots.push(SnapshotStatus { info, diff, error });
+    }
+
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.info.created_at));
+    Ok(snapshots)
+}
+
+/// Replaces the agent's skills directory with the snapshot contents.
+///
+/// The archive is unpacked next to the target first and swapped in with
+/// renames, so a failure leaves the current directory untouched. The state
+/// being replaced is snapshotted first so the restore can itself be undone.
+pub fn restore_snapshot(id: &str) -> Result<SnapshotInfo> {
+    let dir = snapshots_dir()?;
+    let info = read_info(&dir, id)?;
+    let target = PathBuf::from(&info.skills_dir);
+    let parent = target
+        .parent()
+        .ok_or_else(|| Error::InvalidInput(format!("Invalid skills directory: {}", info.skills_dir)))?;
+    fs::create_dir_all(parent).map_err(|e| Error::io("create", parent, e))?;
+
+    if target.is_dir() {
+        create_snapshot(
+            &info.agent,
+            info.scope == "global",
+            info.project_path.as_deref(),
+            Some(format!("Before restoring {}", info.id)),
+        )?;
+    }
+
+    // Unpack on the same filesystem as the target so the final swap is a rename.
+    let staging = tempfile::Builder::new()
+        .prefix(".skillduck-restore-")
+        .tempdir_in(parent)
+        .map_err(|e| Error::io("create", parent, e))?;
+    let archive = archive_path(&dir, id);
+    let file = File::open(&archive).map_err(|e| Error::io("open", &archive, e))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(staging.path())
+        .map_err(|e| Error::io("unpack", &archive, e))?;
+    let restored = staging.path().join(ARCHIVE_ROOT);
+
+    let backup = staging.path().join("previous");
+    let had_target = target.exists() || target.symlink_metadata().is_ok();
+    if had_target {
+        fs::rename(&target, &backup).map_err(|e| Error::io("move", &target, e))?;
+    }
+    if let Err(e) = fs::rename(&restored, &target) {
+        if had_target {
+            let _ = fs::rename(&backup, &target);
+        }
+        return Err(Error::io("restore", &target, e));
+    }
+
+    // `staging` (holding the previous directory) is removed on drop.
+    Ok(info)
+}
+
+pub fn delete_snapshot(id: &str) -> Result<()> {
+    let dir = snapshots_dir()?;
+    read_info(&dir, id)?;
+    for path in [archive_path(&dir, id), meta_path(&dir, id)] {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| Error::io("remove", &path, e))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::agents::{find_agent, AgentDefinition};
+use super::fs::copy_dir_recursive;
+use super::project_config::{ProjectConfig, PROJECT_CONFIG_FILE};
+use super::{Error, Result};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncAction {
+    pub skill: String,
+    pub agent: String,
+    pub source: String,
+    pub destination: String,
+    /// One of "copied", "planned", "exists", "shared" (same directory as the
+    /// source agent) or "missing" (a skill the project requires that the
+    /// source agent does not have; `source` is where to install it from).
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub from_agent: String,
+    pub dry_run: bool,
+    pub actions: Vec<SyncAction>,
+}
+
+pub fn skills_base_dir(agent: &AgentDefinition, global: bool, project_path: Option<&str>) -> Result<PathBuf> {
+    if global {
+        let home = dirs::home_dir().ok_or(Error::HomeDirUnavailable)?;
+        Ok(home.join(&agent.global_path))
+    } else {
+        let project = project_path
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| Error::InvalidInput("Project path is required for project scope".into()))?;
+        Ok(Path::new(project).join(&agent.project_path))
+    }
+}
+
+/// Returns every skill directory (relative to `base`) that contains a SKILL.md.
+pub fn find_skill_dirs(base: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if !base.exists() {
+        return dirs;
+    }
+    for entry in WalkDir::new(base).follow_links(true).max_depth(4).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_name() == "SKILL.md" {
+            if let Some(rel) = entry.path().parent().and_then(|p| p.strip_prefix(base).ok()) {
+                if !rel.as_os_str().is_empty() {
+                    dirs.push(rel.to_path_buf());
+                }
+            }
+        }
+    }
+    dirs.sort();
+    dirs
+}
+
+/// Copies every skill installed for `from_agent` into each of `to_agents`,
+/// skipping skills the target already has.
+///
+/// For a project with a `.skillduck.toml`, `to_agents` defaults to the
+/// agents it targets, skills under ignored paths are left out, and required
+/// skills neither agent has are reported as missing.
+pub fn sync_skills(
+    from_agent: &str,
+    to_agents: &[String],
+    global: bool,
+    project_path: Option<&str>,
+    dry_run: bool,
+) -> Result<SyncReport> {
+    let source_agent = find_agent(from_agent)?;
+    let source_base = skills_base_dir(&source_agent, global, project_path)?;
+    let config = match project_path {
+        Some(project) if !global => ProjectConfig::load(Path::new(project))?,
+        _ => None,
+    };
+
+    let mut skill_dirs = find_skill_dirs(&source_base);
+    let mut to_agents = to_agents.to_vec();
+    if let Some(config) = &config {
+        skill_dirs.retain(|rel| !config.is_ignored(&source_base.join(rel)));
+        if to_agents.is_empty() {
+            to_agents = config.agents.iter().filter(|a| *a != from_agent).cloned().collect();
+        }
+        if let Some(agent) = to_agents.iter().find(|a| !config.targets(a)) {
+            return Err(Error::InvalidInput(format!(
+                "{} is not one of the agents targeted by {}",
+                agent,
+                config.root.join(PROJECT_CONFIG_FILE).display()
+            )));
+        }
+    }
+    if to_agents.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "No target agents given, and the project has no agents listed in {}",
+            PROJECT_CONFIG_FILE
+        )));
+    }
+
+    let mut actions = Vec::new();
+
+    for target_id in &to_agents {
+        let target_agent = find_agent(target_id)?;
+        let target_base = skills_base_dir(&target_agent, global, project_path)?;
+
+        for rel in &skill_dirs {
+            let source = source_base.join(rel);
+            let destination = target_base.join(rel);
+
+            let status = if target_base == source_base {
+                "shared"
+            } else if destination.join("SKILL.md").exists() {
+                "exists"
+            } else if dry_run {
+                "planned"
+            } else {
+                copy_dir_recursive(&source, &destination)?;
+                "copied"
+            };
+
+            actions.push(SyncAction {
+                skill: rel.to_string_lossy().to_string(),
+                agent: target_agent.id.clone(),
+                source: source.to_string_lossy().to_string(),
+                destination: destination.to_string_lossy().to_string(),
+                status: status.to_string(),
+            });
+        }
+
+        let target_dirs = find_skill_dirs(&target_base);
+        for skill in config.iter().flat_map(|c| &c.required_skills) {
+            let named = |rel: &PathBuf| rel.file_name().is_some_and(|n| *n == *skill.name);
+            if skill_dirs.iter().any(named) || target_dirs.iter().any(named) {
+                continue;
+            }
+            actions.push(SyncAction {
+                skill: skill.name.clone(),
+                agent: target_agent.id.clone(),
+                source: skill.source.clone().unwrap_or_default(),
+                destination: target_base.join(&skill.name).to_string_lossy().to_string(),
+                status: "missing".to_string(),
+            });
+        }
+    }
+
+    Ok(SyncReport {
+        from_agent: from_agent.to_string(),
+        dry_run,
+        actions,
+    })
+}
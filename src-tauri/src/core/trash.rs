@@ -0,0 +1,236 @@
+//! Reversible skill removal.
+//!
+//! Removing a skill moves its directory for every selected agent into
+//! `trash/<id>/` in the data directory, next to a `meta.json` describing where each
+//! piece came from, so it can be restored later.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::agents::find_agent;
+use super::fs::{move_path, write_atomic};
+use super::sync::{find_skill_dirs, skills_base_dir};
+use super::{paths, Error, Result};
+
+const META_FILE: &str = "meta.json";
+
+/// One removed location of a skill. Agents sharing a skills directory share an item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedItem {
+    pub original_path: String,
+    pub agents: Vec<String>,
+    /// Directory name inside the trash entry holding the skill, if it was a real directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stored_as: Option<String>,
+    /// Target of the original path when it was a symlink (only the link is removed).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    pub skill_name: String,
+    pub agents: Vec<String>,
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+    /// Unix time in milliseconds.
+    pub removed_at: u64,
+    pub items: Vec<TrashedItem>,
+}
+
+fn trash_dir() -> Result<PathBuf> {
+    paths::data_subdir("trash")
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Finds the directory of `skill_name` under an agent's skills directory,
+/// either directly at `<base>/<name>` or nested deeper.
+fn locate_skill_dir(base: &Path, skill_name: &str) -> Option<PathBuf> {
+    let direct = base.join(skill_name);
+    if direct.join("SKILL.md").exists() {
+        return Some(direct);
+    }
+    find_skill_dirs(base)
+        .into_iter()
+        .find(|rel| rel.file_name().map(|n| n == skill_name).unwrap_or(false))
+        .map(|rel| base.join(rel))
+}
+
+fn read_entry(dir: &Path) -> Result<TrashEntry> {
+    let meta = dir.join(META_FILE);
+    let content = fs::read_to_string(&meta).map_err(|e| Error::io("read", &meta, e))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Moves a skill installed for `agents` into the trash.
+pub fn remove_skill(
+    skill_name: &str,
+    agents: &[String],
+    global: bool,
+    project_path: Option<&str>,
+) -> Result<TrashEntry> {
+    if skill_name.is_empty() || skill_name.contains(['/', '\\']) || skill_name == ".." {
+        return Err(Error::InvalidInput(format!("Invalid skill name: {}", skill_name)));
+    }
+
+    // Collect every distinct location first so shared directories are moved once.
+    let mut items: Vec<(PathBuf, TrashedItem)> = Vec::new();
+    for agent_id in agents {
+        let agent = find_agent(agent_id)?;
+        let base = skills_base_dir(&agent, global, project_path)?;
+        let Some(dir) = locate_skill_dir(&base, skill_name) else {
+            continue;
+        };
+
+        if let Some((_, item)) = items.iter_mut().find(|(p, _)| *p == dir) {
+            item.agents.push(agent.id.clone());
+            continue;
+        }
+
+        let symlink_target = fs::read_link(&dir).ok().map(|t| t.to_string_lossy().to_string());
+        items.push((
+            dir.clone(),
+            TrashedItem {
+                original_path: dir.to_string_lossy().to_string(),
+                agents: vec![agent.id.clone()],
+                stored_as: None,
+                symlink_target,
+            },
+        ));
+    }
+
+    if items.is_empty() {
+        return Err(Error::NotFound(PathBuf::from(skill_name)));
+    }
+
+    let removed_at = now_ms();
+    let id = format!("{}-{}", removed_at, skill_name);
+    let entry_dir = trash_dir()?.join(&id);
+    fs::create_dir_all(&entry_dir).map_err(|e| Error::io("create", &entry_dir, e))?;
+
+    let mut entry = TrashEntry {
+        id,
+        skill_name: skill_name.to_string(),
+        agents: Vec::new(),
+        scope: if global { "global" } else { "project" }.to_string(),
+        project_root: if global { None } else { project_path.map(|p| p.to_string()) },
+        removed_at,
+        items: Vec::new(),
+    };
+
+    // Written before anything moves and again after each item, so whatever
+    // reached the trash can be restored even if a later item fails.
+    let meta = entry_dir.join(META_FILE);
+    write_atomic(&meta, serde_json::to_string_pretty(&entry)?)?;
+    for (index, (path, mut item)) in items.into_iter().enumerate() {
+        if item.symlink_target.is_some() {
+            fs::remove_file(&path).map_err(|e| Error::io("remove", &path, e))?;
+        } else {
+            let stored_as = index.to_string();
+            move_path(&path, &entry_dir.join(&stored_as))?;
+            item.stored_as = Some(stored_as);
+        }
+        for agent in &item.agents {
+            if !entry.agents.contains(agent) {
+                entry.agents.push(agent.clone());
+            }
+        }
+        entry.items.push(item);
+        write_atomic(&meta, serde_json::to_string_pretty(&entry)?)?;
+    }
+
+    Ok(entry)
+}
+
+/// Lists trashed skills, most recently removed first.
+pub fn list_trash() -> Result<Vec<TrashEntry>> {
+    let dir = trash_dir()?;
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| Error::io("read", &dir, e))?.flatten() {
+        if entry.path().is_dir() {
+            if let Ok(trash_entry) = read_entry(&entry.path()) {
+                entries.push(trash_entry);
+            }
+        }
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.removed_at));
+    Ok(entries)
+}
+
+fn entry_dir(id: &str) -> Result<PathBuf> {
+    if id.is_empty() || id.contains(['/', '\\']) || id == ".." {
+        return Err(Error::InvalidInput(format!("Invalid trash id: {}", id)));
+    }
+    let dir = trash_dir()?.join(id);
+    if !dir.join(META_FILE).exists() {
+        return Err(Error::NotFound(dir));
+    }
+    Ok(dir)
+}
+
+/// Puts every location of a trashed skill back. Fails without changing
+/// anything if one of the original paths is occupied again.
+pub fn restore_skill(id: &str) -> Result<TrashEntry> {
+    let dir = entry_dir(id)?;
+    let entry = read_entry(&dir)?;
+
+    for item in &entry.items {
+        let original = Path::new(&item.original_path);
+        if original.exists() || original.symlink_metadata().is_ok() {
+            return Err(Error::io(
+                "restore",
+                original,
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, "a file already exists at this path"),
+            ));
+        }
+    }
+
+    for item in &entry.items {
+        let original = Path::new(&item.original_path);
+        if let Some(parent) = original.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io("create", parent, e))?;
+        }
+        if let Some(target) = &item.symlink_target {
+            symlink_dir(Path::new(target), original)?;
+        } else if let Some(stored_as) = &item.stored_as {
+            move_path(&dir.join(stored_as), original)?;
+        }
+    }
+
+    fs::remove_dir_all(&dir).map_err(|e| Error::io("remove", &dir, e))?;
+    Ok(entry)
+}
+
+/// Permanently deletes everything in the trash. Returns the number of entries removed.
+pub fn empty_trash() -> Result<usize> {
+    let dir = trash_dir()?;
+    let mut removed = 0;
+    for entry in fs::read_dir(&dir).map_err(|e| Error::io("read", &dir, e))?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path).map_err(|e| Error::io("remove", &path, e))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(unix)]
+fn symlink_dir(target: &Path, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link).map_err(|e| Error::io("link", link, e))
+}
+
+#[cfg(windows)]
+fn symlink_dir(target: &Path, link: &Path) -> Result<()> {
+    std::os::windows::fs::symlink_dir(target, link).map_err(|e| Error::io("link", link, e))
+}
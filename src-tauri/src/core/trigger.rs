@@ -0,0 +1,256 @@
+//! Offline estimate of which skill a prompt would trigger.
+//!
+//! Agents pick a skill from its `name` and `description` alone, so skills are
+//! ranked by BM25 between the prompt and those two fields (the name counts
+//! twice). Pairs of skills whose descriptions share much of their vocabulary
+//! are reported as overlapping: they compete for the same prompts.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use super::skills::{agent_skills, Skill};
+use super::{Error, Result};
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Cosine similarity from which two skills count as overlapping.
+const OVERLAP_THRESHOLD: f64 = 0.3;
+
+/// Top matches scoring within this fraction of each other make a prompt ambiguous.
+const AMBIGUOUS_RATIO: f64 = 0.8;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "after", "all", "an", "and", "any", "are", "as", "at", "be", "but", "by", "can", "do", "does",
+    "for", "from", "has", "have", "how", "i", "if", "in", "into", "is", "it", "its", "me", "my", "of", "on", "or",
+    "our", "please", "should", "so", "some", "that", "the", "their", "them", "then", "these", "this", "to", "use",
+    "used", "using", "want", "was", "we", "what", "when", "which", "while", "who", "will", "with", "would", "you",
+    "your",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerMatch {
+    pub name: String,
+    pub install_path: String,
+    pub score: f64,
+    /// Prompt terms found in the skill's name or description.
+    pub matched_terms: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillOverlap {
+    pub a: String,
+    pub b: String,
+    /// Cosine similarity of the two skills' TF-IDF vectors, from 0 to 1.
+    pub similarity: f64,
+    pub shared_terms: Vec<String>,
+    /// Both skills match the prompt.
+    pub competing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerReport {
+    pub prompt: String,
+    pub agent: String,
+    /// Skills matching the prompt, best first.
+    pub matches: Vec<TriggerMatch>,
+    /// The best two matches score too close to tell apart.
+    pub ambiguous: bool,
+    pub overlaps: Vec<SkillOverlap>,
+}
+
+/// Lowercased words without stop words, with common suffixes stripped so
+/// "converting", "converts" and "convert" match.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.len() > 1 && !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+        .collect()
+}
+
+fn stem(word: &str) -> String {
+    for suffix in ["ing", "ed", "s"] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            if stem.len() >= 3 && !stem.ends_with('s') {
+                return stem.to_string();
+            }
+        }
+    }
+    word.to_string()
+}
+
+struct Corpus {
+    docs: Vec<HashMap<String, usize>>,
+    lengths: Vec<usize>,
+    doc_freq: HashMap<String, usize>,
+    avg_len: f64,
+}
+
+impl Corpus {
+    fn new(skills: &[Skill]) -> Corpus {
+        let mut docs = Vec::new();
+        let mut lengths = Vec::new();
+        let mut doc_freq: HashMap<String, usize> = HashMap::new();
+        for skill in skills {
+            let text = format!("{0} {0} {1}", skill.name, skill.description);
+            let terms = tokenize(&text);
+            lengths.push(terms.len());
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            for term in terms {
+                *counts.entry(term).or_default() += 1;
+            }
+            for term in counts.keys() {
+                *doc_freq.entry(term.clone()).or_default() += 1;
+            }
+            docs.push(counts);
+        }
+        let avg_len = lengths.iter().sum::<usize>() as f64 / lengths.len().max(1) as f64;
+        Corpus {
+            docs,
+            lengths,
+            doc_freq,
+            avg_len,
+        }
+    }
+
+    fn idf(&self, term: &str) -> f64 {
+        let n = self.docs.len() as f64;
+        let df = self.doc_freq.get(term).copied().unwrap_or(0) as f64;
+        (1.0 + (n - df + 0.5) / (df + 0.5)).ln()
+    }
+
+    fn bm25(&self, doc: usize, query: &BTreeSet<String>) -> f64 {
+        let len_norm = 1.0 - B + B * self.lengths[doc] as f64 / self.avg_len.max(1.0);
+        query
+            .iter()
+            .filter_map(|term| self.docs[doc].get(term).map(|&tf| (term, tf as f64)))
+            .map(|(term, tf)| self.idf(term) * tf * (K1 + 1.0) / (tf + K1 * len_norm))
+            .sum()
+    }
+
+    fn tfidf(&self, doc: usize) -> HashMap<&str, f64> {
+        self.docs[doc]
+            .iter()
+            .map(|(term, &tf)| (term.as_str(), tf as f64 * self.idf(term)))
+            .collect()
+    }
+}
+
+fn cosine(a: &HashMap<&str, f64>, b: &HashMap<&str, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(term, x)| b.get(term).map(|y| x * y)).sum();
+    let norm = |v: &HashMap<&str, f64>| v.values().map(|x| x * x).sum::<f64>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom == 0.0 {
+        0.0
+    } else {
+        dot / denom
+    }
+}
+
+/// Ranks the skills of `skills` against `prompt`.
+pub fn rank_skills(prompt: &str, agent: &str, skills: &[Skill]) -> TriggerReport {
+    let corpus = Corpus::new(skills);
+    let query: BTreeSet<String> = tokenize(prompt).into_iter().collect();
+
+    let mut matches: Vec<TriggerMatch> = skills
+        .iter()
+        .enumerate()
+        .map(|(i, skill)| TriggerMatch {
+            name: skill.name.clone(),
+            install_path: skill.install_path.clone(),
+            score: corpus.bm25(i, &query),
+            matched_terms: query.iter().filter(|t| corpus.docs[i].contains_key(*t)).cloned().collect(),
+        })
+        .filter(|m| m.score > 0.0)
+        .collect();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let ambiguous = matches.len() > 1 && matches[1].score >= matches[0].score * AMBIGUOUS_RATIO;
+
+    let vectors: Vec<HashMap<&str, f64>> = (0..skills.len()).map(|i| corpus.tfidf(i)).collect();
+    let mut overlaps = Vec::new();
+    for i in 0..skills.len() {
+        for j in i + 1..skills.len() {
+            let similarity = cosine(&vectors[i], &vectors[j]);
+            if similarity < OVERLAP_THRESHOLD {
+                continue;
+            }
+            let mut shared_terms: Vec<String> =
+                corpus.docs[i].keys().filter(|t| corpus.docs[j].contains_key(*t)).cloned().collect();
+            shared_terms.sort();
+            let matched = |name: &str| matches.iter().any(|m| m.name == name);
+            overlaps.push(SkillOverlap {
+                a: skills[i].name.clone(),
+                b: skills[j].name.clone(),
+                similarity,
+                shared_terms,
+                competing: matched(&skills[i].name) && matched(&skills[j].name),
+            });
+        }
+    }
+    overlaps.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+    TriggerReport {
+        prompt: prompt.to_string(),
+        agent: agent.to_string(),
+        matches,
+        ambiguous,
+        overlaps,
+    }
+}
+
+/// Ranks every skill installed for `agent_id` (globally and, if given, in
+/// `project_path`) against `prompt`.
+pub fn simulate_trigger(prompt: &str, agent_id: &str, project_path: Option<&str>) -> Result<TriggerReport> {
+    if prompt.trim().is_empty() {
+        return Err(Error::InvalidInput("Prompt is empty".into()));
+    }
+    let skills = agent_skills(agent_id, project_path)?;
+    Ok(rank_skills(prompt, agent_id, &skills))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skill(name: &str, description: &str) -> Skill {
+        Skill {
+            description: description.into(),
+            ..Skill::for_test(name, &format!("/skills/{}/SKILL.md", name))
+        }
+    }
+
+    #[test]
+    fn ranks_the_closest_skill_first() {
+        let skills = [
+            skill("spreadsheet", "Edit Excel spreadsheets and CSV files"),
+            skill("pdf", "Extract text and tables from PDF documents, fill PDF forms"),
+            skill("slides", "Create presentation slides from an outline"),
+        ];
+        let report = rank_skills("extract the tables from this pdf", "claude-code", &skills);
+
+        let names: Vec<&str> = report.matches.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["pdf"]);
+        assert_eq!(report.matches[0].matched_terms, ["extract", "pdf", "table"]);
+        assert!(!report.ambiguous);
+    }
+
+    #[test]
+    fn name_matches_outrank_description_matches() {
+        let skills = [
+            skill("changelog", "Summarize commits for a release"),
+            skill("release", "Tag and publish a new version"),
+        ];
+        let report = rank_skills("prepare the release", "claude-code", &skills);
+
+        let names: Vec<&str> = report.matches.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["release", "changelog"]);
+        assert!(report.matches[0].score > report.matches[1].score);
+    }
+
+    #[test]
+    fn stems_word_forms_together() {
+        assert_eq!(tokenize("Converting converts convert"), ["convert", "convert", "convert"]);
+    }
+}
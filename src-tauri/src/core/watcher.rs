@@ -0,0 +1,667 @@
+//! Watching directories for changes to installed skills.
+//!
+//! [`WatchService`] keeps a watch on every agent's global skills directory,
+//! every scan root and any directory watched on request. Raw filesystem
+//! events are collected until the tree has been quiet for [`DEBOUNCE`] (or
+//! for at most [`MAX_DELAY`]), merged per path so an editor's save sequence
+//! becomes a single event, and reported for files inside a skill directory
+//! (a directory containing `SKILL.md`) together with that directory. Changes
+//! to a project's `.skillduck.toml` are reported too, with the project
+//! directory in place of the skill directory.
+
+use notify::event::{EventKind, MetadataKind, ModifyKind, RenameMode};
+use notify::{recommended_watcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+use super::agents::{get_agent_definitions, home_roots};
+use super::fs::is_ignored_dir;
+use super::project_config::PROJECT_CONFIG_FILE;
+use super::{settings, Error, Result};
+
+/// Quiet time after the last event before a batch is reported.
+pub const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Longest a change waits while events keep arriving.
+pub const MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// How deep below a watched root skill directories are looked for.
+const MAX_SKILL_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillChange {
+    pub kind: ChangeKind,
+    /// The skill directory the file belongs to, or for a project config, the
+    /// project directory.
+    pub skill_dir: String,
+    /// The affected file or directory.
+    pub path: String,
+    /// Previous path of a renamed file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pending {
+    kind: ChangeKind,
+    from: Option<PathBuf>,
+}
+
+/// Raw events merged per path.
+#[derive(Default)]
+struct Batch {
+    changes: BTreeMap<PathBuf, Pending>,
+    /// Paths that did not exist when the batch started.
+    created: BTreeSet<PathBuf>,
+}
+
+impl Batch {
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn record(&mut self, path: PathBuf, kind: ChangeKind) {
+        use ChangeKind::*;
+
+        if kind == Created && !self.changes.contains_key(&path) {
+            self.created.insert(path.clone());
+        }
+        let merged = match (self.changes.get(&path).map(|p| p.kind), kind) {
+            (None, kind) => Some(kind),
+            (Some(Created), Modified) => Some(Created),
+            (Some(Created), Removed) => None,
+            (Some(Removed), Created) => Some(Modified),
+            (Some(Renamed), Modified) => Some(Renamed),
+            // Moved aside and deleted, as editors do with backups.
+            (Some(Renamed), Removed) => None,
+            (Some(_), kind) => Some(kind),
+        };
+        match merged {
+            Some(kind) => {
+                let from = self.changes.get(&path).and_then(|p| p.from.clone()).filter(|_| kind == Renamed);
+                self.changes.insert(path, Pending { kind, from });
+            }
+            None => {
+                self.changes.remove(&path);
+            }
+        }
+    }
+
+    fn record_rename(&mut self, from: PathBuf, to: PathBuf) {
+        // Backends that also report each side of the rename on its own have
+        // already recorded them; the rename replaces those records.
+        let pending = if self.created.contains(&from) {
+            self.changes.remove(&from);
+            // A file written next to its target and renamed over it: the
+            // usual atomic save.
+            Pending {
+                kind: ChangeKind::Modified,
+                from: None,
+            }
+        } else {
+            self.changes.insert(
+                from.clone(),
+                Pending {
+                    kind: ChangeKind::Removed,
+                    from: None,
+                },
+            );
+            Pending {
+                kind: ChangeKind::Renamed,
+                from: Some(from),
+            }
+        };
+        self.changes.insert(to, pending);
+    }
+
+    fn add(&mut self, event: notify::Event) {
+        let mut paths = event.paths.into_iter();
+        match event.kind {
+            EventKind::Create(_) => paths.for_each(|p| self.record(p, ChangeKind::Created)),
+            EventKind::Remove(_) => paths.for_each(|p| self.record(p, ChangeKind::Removed)),
+            EventKind::Modify(ModifyKind::Name(mode)) => match (mode, paths.next(), paths.next()) {
+                (RenameMode::Both, Some(from), Some(to)) => self.record_rename(from, to),
+                (RenameMode::From, Some(path), _) => self.record(path, ChangeKind::Removed),
+                (RenameMode::To, Some(path), _) => self.record(path, ChangeKind::Created),
+                (_, first, second) => {
+                    // The backend could not tell which side this is.
+                    for path in first.into_iter().chain(second).chain(paths) {
+                        let kind = if path.exists() { ChangeKind::Created } else { ChangeKind::Removed };
+                        self.record(path, kind);
+                    }
+                }
+            },
+            EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)) => {}
+            EventKind::Modify(_) => paths.for_each(|p| self.record(p, ChangeKind::Modified)),
+            EventKind::Access(_) | EventKind::Any | EventKind::Other => {}
+        }
+    }
+}
+
+/// Skill directories below a watched root, kept up to date as skills come
+/// and go so changes inside removed skills can still be attributed.
+struct SkillIndex {
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl SkillIndex {
+    fn scan(root: &Path) -> BTreeSet<PathBuf> {
+        WalkDir::new(root)
+            .follow_links(true)
+            .max_depth(MAX_SKILL_DEPTH)
+            .into_iter()
+            .filter_entry(|e| !(e.file_type().is_dir() && is_ignored_dir(&e.file_name().to_string_lossy())))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name() == "SKILL.md")
+            .filter_map(|e| e.path().parent().map(Path::to_path_buf))
+            .collect()
+    }
+
+    /// The innermost known skill directory containing `path` (or being it).
+    fn owner(&self, path: &Path) -> Option<&PathBuf> {
+        self.dirs.range(..=path.to_path_buf()).rev().find(|dir| path.starts_with(dir))
+    }
+
+    /// Indexes the skills at or below `path`, returning those not known yet.
+    fn add_skills_in(&mut self, path: &Path) -> Vec<PathBuf> {
+        let found = if path.file_name().is_some_and(|n| n == "SKILL.md") {
+            path.parent().map(Path::to_path_buf).into_iter().collect()
+        } else if path.is_dir() {
+            SkillIndex::scan(path)
+        } else {
+            BTreeSet::new()
+        };
+        found.into_iter().filter(|dir| self.dirs.insert(dir.clone())).collect()
+    }
+
+    fn remove_skills_in(&mut self, path: &Path) {
+        if path.file_name().is_some_and(|n| n == "SKILL.md") {
+            if let Some(parent) = path.parent() {
+                self.dirs.remove(parent);
+            }
+        } else {
+            self.dirs.retain(|dir| !dir.starts_with(path));
+        }
+    }
+
+    /// Turns a batch into skill changes and updates the index with it.
+    fn resolve(&mut self, batch: Batch) -> Vec<SkillChange> {
+        let mut found = Vec::new();
+        for (path, pending) in &batch.changes {
+            if pending.kind != ChangeKind::Removed {
+                found.extend(self.add_skills_in(path));
+            }
+        }
+
+        let mut changes: Vec<SkillChange> = batch
+            .changes
+            .iter()
+            .filter_map(|(path, pending)| {
+                let owner = match self.owner(path) {
+                    Some(owner) => owner,
+                    None if path.file_name().is_some_and(|n| n == PROJECT_CONFIG_FILE) => path.parent()?,
+                    None => return None,
+                };
+                Some(SkillChange {
+                    kind: pending.kind,
+                    skill_dir: owner.to_string_lossy().to_string(),
+                    path: path.to_string_lossy().to_string(),
+                    from: pending.from.as_ref().map(|p| p.to_string_lossy().to_string()),
+                })
+            })
+            .collect();
+
+        // Skills in a directory tree created at once may have no events of their own.
+        for dir in found {
+            let skill_dir = dir.to_string_lossy().to_string();
+            if !changes.iter().any(|c| c.skill_dir == skill_dir) {
+                changes.push(SkillChange {
+                    kind: ChangeKind::Created,
+                    path: skill_dir.clone(),
+                    skill_dir,
+                    from: None,
+                });
+            }
+        }
+
+        for (path, pending) in &batch.changes {
+            if pending.kind == ChangeKind::Removed {
+                self.remove_skills_in(path);
+            }
+            if let Some(from) = &pending.from {
+                self.remove_skills_in(from);
+            }
+        }
+        changes
+    }
+}
+
+fn debounce<F>(events: Receiver<notify::Event>, mut index: SkillIndex, on_changes: F)
+where
+    F: Fn(Vec<SkillChange>),
+{
+    // Ends when the watcher, and with it the sending side, is dropped.
+    while let Ok(first) = events.recv() {
+        let started = Instant::now();
+        let mut batch = Batch::default();
+        batch.add(first);
+
+        loop {
+            let wait = DEBOUNCE.min(MAX_DELAY.saturating_sub(started.elapsed()));
+            match events.recv_timeout(wait) {
+                Ok(event) => batch.add(event),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        if batch.is_empty() {
+            continue;
+        }
+        let changes = index.resolve(batch);
+        if !changes.is_empty() {
+            on_changes(changes);
+        }
+    }
+}
+
+/// A recursive watch on one directory. Dropping it stops the watch.
+pub struct SkillWatcher {
+    root: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+impl SkillWatcher {
+    /// Watches `root` and calls `on_changes` from a background thread with
+    /// each debounced batch of changes to skill files.
+    pub fn new<F>(root: &Path, on_changes: F) -> Result<SkillWatcher>
+    where
+        F: Fn(Vec<SkillChange>) + Send + 'static,
+    {
+        if !root.exists() {
+            return Err(Error::NotFound(root.to_path_buf()));
+        }
+        let watch_error = |source| Error::Watch {
+            path: root.to_path_buf(),
+            source,
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                let _ = tx.send(event);
+            }
+        })
+        .map_err(watch_error)?;
+        watcher.watch(root, RecursiveMode::Recursive).map_err(watch_error)?;
+
+        let index = SkillIndex {
+            dirs: SkillIndex::scan(root),
+        };
+        thread::spawn(move || debounce(rx, index, on_changes));
+
+        Ok(SkillWatcher {
+            root: root.to_path_buf(),
+            _watcher: watcher,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+/// Why a directory is watched, as reported by [`WatchService::list`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchInfo {
+    /// Canonical path of the directory.
+    pub path: String,
+    /// Agents whose global skills directory this is.
+    pub agents: Vec<String>,
+    pub scan_root: bool,
+    /// Watched through [`WatchService::watch`].
+    pub manual: bool,
+    /// Another watched directory containing this one, which covers its events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub covered_by: Option<String>,
+    /// Why the directory could not be watched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+type ChangeHandler = Arc<dyn Fn(Vec<SkillChange>) + Send + Sync>;
+
+/// A non-recursive watch on the nearest existing ancestor of directories
+/// that should be watched but do not exist yet.
+struct PendingWatch {
+    missing: Vec<PathBuf>,
+    _watcher: RecommendedWatcher,
+}
+
+#[derive(Default)]
+struct ServiceState {
+    /// Directories watched on request, with how many watches each owner holds.
+    manual: BTreeMap<PathBuf, BTreeMap<String, usize>>,
+    roots: BTreeMap<PathBuf, WatchInfo>,
+    watchers: BTreeMap<PathBuf, SkillWatcher>,
+    pending: BTreeMap<PathBuf, PendingWatch>,
+}
+
+struct Shared {
+    on_changes: ChangeHandler,
+    state: Mutex<ServiceState>,
+    /// Held for a whole reconcile, so that two never interleave while
+    /// `state` is only locked around the changes to it.
+    reconciling: Mutex<()>,
+    /// Wakes the thread that reconciles when a missing directory appears.
+    appeared: mpsc::Sender<()>,
+}
+
+/// Watches every directory skills are installed in, keyed by canonical path
+/// so a directory is never watched twice.
+///
+/// The watches follow the settings through [`WatchService::reconcile`].
+/// Agent directories and scan roots that do not exist yet are picked up as
+/// soon as they are created, through a watch on their nearest existing
+/// ancestor, so nothing needs to poll.
+pub struct WatchService {
+    shared: Arc<Shared>,
+}
+
+fn canonical(path: &Path) -> Option<PathBuf> {
+    path.canonicalize().ok().filter(|p| p.is_dir())
+}
+
+fn entry(roots: &mut BTreeMap<PathBuf, WatchInfo>, path: PathBuf) -> &mut WatchInfo {
+    roots.entry(path.clone()).or_insert_with(|| WatchInfo {
+        path: path.to_string_lossy().to_string(),
+        ..WatchInfo::default()
+    })
+}
+
+/// Directories that should be watched, as configured.
+#[derive(Default)]
+struct Configured {
+    roots: BTreeMap<PathBuf, WatchInfo>,
+    /// Configured directories that do not exist.
+    missing: Vec<PathBuf>,
+}
+
+impl Configured {
+    fn add(&mut self, path: PathBuf) -> Option<&mut WatchInfo> {
+        match canonical(&path) {
+            Some(canonical) => Some(entry(&mut self.roots, canonical)),
+            None => {
+                if !self.missing.contains(&path) {
+                    self.missing.push(path);
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Watches `ancestor` for the creation of anything leading to `missing`.
+fn watch_ancestor(ancestor: &Path, missing: Vec<PathBuf>, appeared: mpsc::Sender<()>) -> Result<PendingWatch> {
+    let watch_error = |source| Error::Watch {
+        path: ancestor.to_path_buf(),
+        source,
+    };
+    let wanted = missing.clone();
+    let mut watcher = recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else {
+            return;
+        };
+        let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_)))
+            && event.paths.iter().any(|p| wanted.iter().any(|m| m.starts_with(p)));
+        if relevant {
+            let _ = appeared.send(());
+        }
+    })
+    .map_err(watch_error)?;
+    watcher.watch(ancestor, RecursiveMode::NonRecursive).map_err(watch_error)?;
+    Ok(PendingWatch {
+        missing,
+        _watcher: watcher,
+    })
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, ServiceState> {
+        // A panic while holding the lock leaves the maps consistent enough to go on.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn reconcile(&self) {
+        let _reconciling = self.reconciling.lock().unwrap_or_else(|e| e.into_inner());
+        // A directory may appear between the check and the watch on its
+        // parent; go round again until nothing did.
+        for _ in 0..8 {
+            let previous = self.lock().roots.clone();
+            let configured = Shared::configured(&previous);
+            let (roots, new) = self.plan(configured.roots);
+            // Each new watcher indexes the skills below its root, which takes
+            // a while on a large scan root; the state stays unlocked meanwhile.
+            let built = new
+                .into_iter()
+                .map(|path| {
+                    let on_changes = self.on_changes.clone();
+                    let watcher = SkillWatcher::new(&path, move |changes| on_changes(changes));
+                    (path, watcher)
+                })
+                .collect();
+
+            let mut state = self.lock();
+            Shared::install(&mut state, roots, built);
+            if !self.watch_missing(&mut state, configured.missing) {
+                break;
+            }
+        }
+    }
+
+    /// Every agent's global skills directory and every scan root. When the
+    /// settings cannot be read, the agent directories and scan roots of
+    /// `previous` stay, rather than every watch going away.
+    fn configured(previous: &BTreeMap<PathBuf, WatchInfo>) -> Configured {
+        Shared::read_configured().unwrap_or_else(|_| Configured {
+            roots: previous
+                .iter()
+                .filter(|(_, info)| !info.agents.is_empty() || info.scan_root)
+                .map(|(path, info)| {
+                    let info = WatchInfo {
+                        path: info.path.clone(),
+                        agents: info.agents.clone(),
+                        scan_root: info.scan_root,
+                        ..WatchInfo::default()
+                    };
+                    (path.clone(), info)
+                })
+                .collect(),
+            missing: Vec::new(),
+        })
+    }
+
+    fn read_configured() -> Result<Configured> {
+        let settings = settings::current()?;
+        let homes = home_roots(&settings.home_roots)?;
+
+        let mut configured = Configured::default();
+        for agent in get_agent_definitions() {
+            for home in &homes {
+                if let Some(info) = configured.add(home.path.join(&agent.global_path)) {
+                    if !info.agents.contains(&agent.id) {
+                        info.agents.push(agent.id.clone());
+                    }
+                }
+            }
+        }
+        for root in &settings.scan_roots {
+            if let Some(info) = configured.add(PathBuf::from(root)) {
+                info.scan_root = true;
+            }
+        }
+        Ok(configured)
+    }
+
+    /// Adds the manual watches to `roots` and drops the watchers no longer
+    /// needed. Returns the roots and the directories that need a new watcher.
+    fn plan(&self, mut roots: BTreeMap<PathBuf, WatchInfo>) -> (BTreeMap<PathBuf, WatchInfo>, Vec<PathBuf>) {
+        let mut state = self.lock();
+        for path in state.manual.keys().filter(|p| p.is_dir()) {
+            entry(&mut roots, path.clone()).manual = true;
+        }
+
+        let paths: Vec<PathBuf> = roots.keys().cloned().collect();
+        for (path, info) in roots.iter_mut() {
+            let outer = paths.iter().find(|other| *other != path && path.starts_with(other));
+            info.covered_by = outer.map(|p| p.to_string_lossy().to_string());
+        }
+
+        state.watchers.retain(|path, _| roots.get(path).is_some_and(|info| info.covered_by.is_none()));
+        let new = roots
+            .iter()
+            .filter(|(path, info)| info.covered_by.is_none() && !state.watchers.contains_key(*path))
+            .map(|(path, _)| path.clone())
+            .collect();
+        (roots, new)
+    }
+
+    /// Swaps in the watchers built for `roots`, recording why any could not
+    /// be built.
+    fn install(
+        state: &mut ServiceState,
+        mut roots: BTreeMap<PathBuf, WatchInfo>,
+        built: Vec<(PathBuf, Result<SkillWatcher>)>,
+    ) {
+        for (path, watcher) in built {
+            match watcher {
+                Ok(watcher) => {
+                    state.watchers.insert(path, watcher);
+                }
+                Err(e) => {
+                    if let Some(info) = roots.get_mut(&path) {
+                        info.error = Some(e.to_string());
+                    }
+                }
+            }
+        }
+        state.roots = roots;
+    }
+
+    /// Watches the nearest existing ancestor of each missing directory.
+    /// Returns whether one of them exists by now.
+    fn watch_missing(&self, state: &mut ServiceState, missing: Vec<PathBuf>) -> bool {
+        let mut by_ancestor: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for path in missing {
+            if let Some(ancestor) = path.ancestors().skip(1).find(|a| a.is_dir()) {
+                by_ancestor.entry(ancestor.to_path_buf()).or_default().push(path);
+            }
+        }
+
+        state
+            .pending
+            .retain(|ancestor, pending| by_ancestor.get(ancestor) == Some(&pending.missing));
+        for (ancestor, missing) in by_ancestor {
+            if state.pending.contains_key(&ancestor) {
+                continue;
+            }
+            // Without the watch the directory is only found on the next
+            // settings change; nothing else depends on it.
+            if let Ok(pending) = watch_ancestor(&ancestor, missing, self.appeared.clone()) {
+                state.pending.insert(ancestor, pending);
+            }
+        }
+
+        state.pending.values().flat_map(|p| &p.missing).any(|p| p.is_dir())
+    }
+}
+
+impl WatchService {
+    pub fn new<F>(on_changes: F) -> WatchService
+    where
+        F: Fn(Vec<SkillChange>) + Send + Sync + 'static,
+    {
+        let (appeared, rx) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            on_changes: Arc::new(on_changes),
+            state: Mutex::new(ServiceState::default()),
+            reconciling: Mutex::new(()),
+            appeared,
+        });
+
+        let weak = Arc::downgrade(&shared);
+        thread::spawn(move || {
+            while rx.recv().is_ok() {
+                // `mkdir -p` and installers create a few levels at once.
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+                match weak.upgrade() {
+                    Some(shared) => shared.reconcile(),
+                    None => break,
+                }
+            }
+        });
+
+        WatchService { shared }
+    }
+
+    /// Brings the watches in line with the agents, the settings and the
+    /// directories that exist. Call it after settings change.
+    pub fn reconcile(&self) {
+        self.shared.reconcile();
+    }
+
+    /// Watches `path` on behalf of `owner`, in addition to the agent
+    /// directories and scan roots. Each call needs its own
+    /// [`WatchService::unwatch`]. Returns the canonical path.
+    pub fn watch(&self, path: &str, owner: &str) -> Result<PathBuf> {
+        let path = canonical(Path::new(path)).ok_or_else(|| Error::NotFound(PathBuf::from(path)))?;
+        {
+            let mut state = self.shared.lock();
+            *state.manual.entry(path.clone()).or_default().entry(owner.to_string()).or_default() += 1;
+        }
+        self.reconcile();
+        Ok(path)
+    }
+
+    /// Releases one watch `owner` took with [`WatchService::watch`]. The
+    /// directory stays watched while anyone else still holds a watch on it,
+    /// and agent directories and scan roots stay watched regardless.
+    pub fn unwatch(&self, path: &str, owner: &str) {
+        let canonical = canonical(Path::new(path)).unwrap_or_else(|| PathBuf::from(path));
+        {
+            let mut state = self.shared.lock();
+            let Some(owners) = state.manual.get_mut(&canonical) else {
+                return;
+            };
+            if let Some(count) = owners.get_mut(owner) {
+                *count -= 1;
+                if *count == 0 {
+                    owners.remove(owner);
+                }
+            }
+            if owners.is_empty() {
+                state.manual.remove(&canonical);
+            }
+        }
+        self.reconcile();
+    }
+
+    pub fn list(&self) -> Vec<WatchInfo> {
+        self.shared.lock().roots.values().cloned().collect()
+    }
+}
@@ -1,13 +1,29 @@
-mod commands;
+#[cfg(feature = "gui")]
+pub mod commands;
+pub mod core;
 
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    core::logging::init();
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(commands::skills::SkillsState::default())
+        .setup(|app| {
+            commands::settings::start(app.handle().clone());
+            commands::watcher::start(app.handle().clone());
+            // Sandboxes left behind by a previous run (e.g. after a crash).
+            std::thread::spawn(|| {
+                if let Err(e) = core::sandbox::cleanup_stale_sandboxes() {
+                    log::warn!("Failed to clean up stale sandboxes: {}", e.describe());
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Skills
             commands::skills::list_skills,
@@ -18,8 +34,15 @@ pub fn run() {
             // Editor
             commands::editor::read_skill_md,
             commands::editor::save_skill_md,
+            commands::editor::close_skill_md,
             commands::editor::validate_skill,
             commands::editor::validate_skill_content,
+            // File history
+            commands::history::list_file_history,
+            commands::history::restore_file_version,
+            // Context preview and trigger simulation
+            commands::context::preview_skill_context,
+            commands::trigger::simulate_trigger,
             // CLI Bridge
             commands::cli_bridge::cli_add_skill,
             commands::cli_bridge::cli_remove_skill,
@@ -28,15 +51,38 @@ pub fn run() {
             commands::cli_bridge::cli_update_skill,
             commands::cli_bridge::cli_init_skill,
             commands::cli_bridge::cli_add_skill_stream,
+            // Sync
+            commands::sync::sync_skills,
+            // Trash
+            commands::trash::remove_skill,
+            commands::trash::list_trash,
+            commands::trash::restore_skill,
+            commands::trash::empty_trash,
+            // Snapshots
+            commands::snapshot::create_snapshot,
+            commands::snapshot::list_snapshots,
+            commands::snapshot::restore_snapshot,
+            commands::snapshot::delete_snapshot,
             // Sandbox
             commands::sandbox::create_sandbox,
             commands::sandbox::run_sandbox_script,
+            commands::sandbox::run_sandbox_script_stream,
+            commands::sandbox::write_sandbox_stdin,
+            commands::sandbox::close_sandbox_stdin,
+            commands::sandbox::kill_sandbox_run,
+            commands::sandbox::read_sandbox_file,
             commands::sandbox::cleanup_sandbox,
+            commands::sandbox::list_sandboxes,
+            // Skill tests
+            commands::skill_tests::run_skill_tests,
             // Watcher
             commands::watcher::watch_skills_dir,
             commands::watcher::unwatch_skills_dir,
+            commands::watcher::list_watches,
             // Settings
             commands::settings::get_settings,
+            commands::settings::get_settings_backup,
+            commands::settings::get_legacy_migration,
             commands::settings::save_settings,
             // Updater
             commands::updater::check_for_app_update,
@@ -2,6 +2,7 @@ import { useEffect } from "react";
 import { Shell } from "@/components/layout/Shell";
 import { useStore } from "@/hooks/useStore";
 import { useUpdates } from "@/hooks/useUpdates";
+import { onSkillsUpdated } from "@/lib/tauri";
 import { SkillsPage } from "@/pages/SkillsPage";
 import { AgentsPage } from "@/pages/AgentsPage";
 import { ExplorePage } from "@/pages/ExplorePage";
@@ -42,6 +43,7 @@ function PageRouter() {
 
 function App() {
   const initGlobalAgents = useStore((s) => s.initGlobalAgents);
+  const applySkillsUpdate = useStore((s) => s.applySkillsUpdate);
   const { checkForUpdates } = useUpdates();
 
   useEffect(() => {
@@ -50,6 +52,14 @@ function App() {
     checkForUpdates().catch(() => {});
   }, []); // eslint-disable-line react-hooks/exhaustive-deps
 
+  // The backend keeps the skill list current as skill directories change.
+  useEffect(() => {
+    const unlisten = onSkillsUpdated(applySkillsUpdate);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [applySkillsUpdate]);
+
   return (
     <QueryClientProvider client={queryClient}>
       <Shell>
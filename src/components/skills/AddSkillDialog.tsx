@@ -16,7 +16,7 @@ import {
   ChevronUp,
   Loader2,
 } from "lucide-react";
-import { cn } from "@/lib/utils";
+import { cn, formatError } from "@/lib/utils";
 
 interface AddSkillDialogProps {
   open: boolean;
@@ -118,7 +118,7 @@ export function AddSkillDialog({
         onSuccess?.();
       }
     } catch (err) {
-      appendCliOutput(`Error: ${err}`);
+      appendCliOutput(`Error: ${formatError(err)}`);
     } finally {
       setAddLoading(false);
       setIsLoading(false);
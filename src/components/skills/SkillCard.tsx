@@ -26,7 +26,7 @@ export function SkillCard({ skill, isSelected, onClick }: SkillCardProps) {
         </div>
         <div className="flex items-center gap-1">
           {skill.scope === "global" ? (
-            <span title="Global"><Globe className="h-3 w-3 text-muted-foreground" /></span>
+            <span title={skill.home ? `Global (${skill.home})` : "Global"}><Globe className="h-3 w-3 text-muted-foreground" /></span>
           ) : (
             <span title="Project"><FolderOpen className="h-3 w-3 text-muted-foreground" /></span>
           )}
@@ -79,6 +79,7 @@ export function SkillDetail({ skill, onRemove, onUpdated }: SkillDetailProps) {
                 <FolderOpen className="h-3 w-3" />
               )}
               {skill.scope}
+              {skill.home && <span className="opacity-70">· {skill.home}</span>}
             </span>
           </div>
           <p className="mt-2 text-sm text-muted-foreground">{skill.description}</p>
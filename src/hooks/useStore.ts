@@ -1,6 +1,6 @@
 import { create } from "zustand";
 import { persist } from "zustand/middleware";
-import type { Skill, AgentInfo, UpdateInfo } from "@/types/skills";
+import type { Skill, AgentInfo, UpdateInfo, SkillsUpdate } from "@/types/skills";
 import type { AppUpdateInfo } from "@/lib/tauri";
 import { detectAgents } from "@/lib/tauri";
 
@@ -16,6 +16,7 @@ interface AppState {
   agentsScanning: boolean;
 
   setSkills: (skills: Skill[]) => void;
+  applySkillsUpdate: (update: SkillsUpdate) => void;
   setAgents: (agents: AgentInfo[]) => void;
   setSelectedSkill: (skill: Skill | null) => void;
   setSelectedAgent: (agent: AgentInfo | null) => void;
@@ -62,6 +63,30 @@ export const useStore = create<AppState>()(
       updatingSkills: false,
 
       setSkills: (skills) => set({ skills }),
+      applySkillsUpdate: (update) =>
+        set((state) => {
+          // Matches the backend's skill_key.
+          const key = (s: Skill) => (s.home ? `${s.scope}::${s.name}::${s.home}` : `${s.scope}::${s.name}`);
+          const removed = new Set(update.removed.map(key));
+          const modified = new Map(update.modified.map((s) => [key(s), s]));
+          const skills = state.skills
+            .filter((s) => !removed.has(key(s)))
+            .map((s) => {
+              const next = modified.get(key(s));
+              // Update checks are not part of the scan; keep their result.
+              return next ? { ...next, has_update: s.has_update } : s;
+            })
+            .concat(update.added)
+            .sort((a, b) => a.name.localeCompare(b.name));
+          const selected = state.selectedSkill;
+          const selectedSkill =
+            selected && removed.has(key(selected))
+              ? null
+              : selected && modified.has(key(selected))
+                ? skills.find((s) => key(s) === key(selected)) ?? selected
+                : selected;
+          return { skills, selectedSkill };
+        }),
       setAgents: (agents) => set({ agents }),
       setSelectedSkill: (skill) => set({ selectedSkill: skill }),
       setSelectedAgent: (agent) => set({ selectedAgent: agent }),
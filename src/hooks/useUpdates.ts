@@ -1,6 +1,7 @@
 import { useStore } from "@/hooks/useStore";
 import { cliCheckUpdates, cliUpdateSkills, cliUpdateSkill, listSkills } from "@/lib/tauri";
 import type { UpdateInfo } from "@/types/skills";
+import { formatError } from "@/lib/utils";
 
 /**
  * Parse `npx skills check` stdout to extract skill names with available updates.
@@ -83,7 +84,7 @@ export function useUpdates() {
         appendCliOutput("All skills are up to date.");
       }
     } catch (err) {
-      appendCliOutput(`Error checking updates: ${err}`);
+      appendCliOutput(`Error checking updates: ${formatError(err)}`);
     } finally {
       setCheckingUpdates(false);
     }
@@ -103,7 +104,7 @@ export function useUpdates() {
       setSkills(refreshed);
       onDone?.();
     } catch (err) {
-      appendCliOutput(`Error updating skills: ${err}`);
+      appendCliOutput(`Error updating skills: ${formatError(err)}`);
     } finally {
       setUpdatingSkills(false);
     }
@@ -123,7 +124,7 @@ export function useUpdates() {
       setSkills(refreshed);
       onDone?.();
     } catch (err) {
-      appendCliOutput(`Error updating ${skillName}: ${err}`);
+      appendCliOutput(`Error updating ${skillName}: ${formatError(err)}`);
     } finally {
       setUpdatingSkills(false);
     }
@@ -1,27 +1,36 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import type { Skill, AgentInfo, CliOutput, ValidationResult, SandboxInfo, ScriptOutput, Settings } from "@/types/skills";
+import type { Skill, AgentInfo, CliOutput, ValidationResult, SandboxInfo, SandboxOptions, SandboxStatus, ScriptOutput, RunOptions, SandboxOutputEvent, SandboxFile, Settings, LegacyMove, SyncReport, TrashEntry, SnapshotInfo, SnapshotStatus, TestReport, ContextPreview, TriggerReport, SkillChange, SkillsUpdate, WatchInfo, FileVersion, SkillDocument, OpenFileChange, HistoryEntry } from "@/types/skills";
 
 // === Direct Rust operations (fast) ===
 
-export async function listSkills(scope: "project" | "global" | "all"): Promise<Skill[]> {
-  return invoke<Skill[]>("list_skills", { scope });
+export async function listSkills(scope: "project" | "global" | "all", refresh?: boolean): Promise<Skill[]> {
+  return invoke<Skill[]>("list_skills", { scope, refresh });
 }
 
 export async function parseSkillMd(path: string): Promise<Skill> {
   return invoke<Skill>("parse_skill_md", { path });
 }
 
-export async function detectAgents(scanRoots?: string[]): Promise<AgentInfo[]> {
-  return invoke<AgentInfo[]>("detect_agents", { scanRoots: scanRoots || null });
+export async function detectAgents(scanRoots?: string[], homeRoots?: string[]): Promise<AgentInfo[]> {
+  return invoke<AgentInfo[]>("detect_agents", { scanRoots: scanRoots || null, homeRoots: homeRoots || null });
 }
 
 export async function getSettings(): Promise<Settings> {
   return invoke<Settings>("get_settings");
 }
 
-export async function saveSettings(settings: Settings): Promise<void> {
-  return invoke<void>("save_settings", { settings });
+export async function getSettingsBackup(): Promise<string | null> {
+  return invoke<string | null>("get_settings_backup");
+}
+
+export async function getLegacyMigration(): Promise<LegacyMove[]> {
+  return invoke<LegacyMove[]>("get_legacy_migration");
+}
+
+/** Resolves to warnings about configured directories that no longer exist */
+export async function saveSettings(settings: Settings): Promise<string[]> {
+  return invoke<string[]>("save_settings", { settings });
 }
 
 export async function validateSkill(path: string): Promise<ValidationResult> {
@@ -39,19 +48,132 @@ export async function validateSkillContent(
 export async function saveSkillMd(
   path: string,
   frontmatter: Record<string, string>,
-  body: string
-): Promise<void> {
-  return invoke<void>("save_skill_md", { path, frontmatter, body });
+  body: string,
+  expectedVersion?: FileVersion | null
+): Promise<FileVersion> {
+  return invoke<FileVersion>("save_skill_md", { path, frontmatter, body, expectedVersion });
+}
+
+export async function readSkillMd(path: string): Promise<SkillDocument> {
+  return invoke<SkillDocument>("read_skill_md", { path });
+}
+
+export async function closeSkillMd(path: string): Promise<void> {
+  return invoke<void>("close_skill_md", { path });
 }
 
-export async function readSkillMd(path: string): Promise<{ frontmatter: Record<string, string>; body: string }> {
-  return invoke("read_skill_md", { path });
+export async function listFileHistory(path: string): Promise<HistoryEntry[]> {
+  return invoke<HistoryEntry[]>("list_file_history", { path });
 }
 
-export async function getSkillsDirectories(): Promise<{ project: string[]; global: string[] }> {
+export async function restoreFileVersion(path: string, id: string): Promise<FileVersion> {
+  return invoke<FileVersion>("restore_file_version", { path, id });
+}
+
+export async function previewSkillContext(
+  skillPath: string,
+  agent: string,
+  projectPath?: string
+): Promise<ContextPreview> {
+  return invoke<ContextPreview>("preview_skill_context", {
+    skillPath,
+    agent,
+    projectPath: projectPath || null,
+  });
+}
+
+export async function simulateTrigger(
+  prompt: string,
+  agent: string,
+  projectPath?: string
+): Promise<TriggerReport> {
+  return invoke<TriggerReport>("simulate_trigger", {
+    prompt,
+    agent,
+    projectPath: projectPath || null,
+  });
+}
+
+export async function getSkillsDirectories(): Promise<{
+  project: string[];
+  global: string[];
+  other_homes: { home: string; global: string[] }[];
+}> {
   return invoke("get_skills_directories");
 }
 
+export async function syncSkills(
+  fromAgent: string,
+  toAgents: string[],
+  global: boolean,
+  projectPath?: string,
+  dryRun = false
+): Promise<SyncReport> {
+  return invoke<SyncReport>("sync_skills", {
+    fromAgent,
+    toAgents,
+    global,
+    projectPath: projectPath || null,
+    dryRun,
+  });
+}
+
+// === Trash ===
+
+export async function removeSkill(
+  skillName: string,
+  agents: string[],
+  global: boolean,
+  projectPath?: string
+): Promise<TrashEntry> {
+  return invoke<TrashEntry>("remove_skill", {
+    skillName,
+    agents,
+    global,
+    projectPath: projectPath || null,
+  });
+}
+
+export async function listTrash(): Promise<TrashEntry[]> {
+  return invoke<TrashEntry[]>("list_trash");
+}
+
+export async function restoreSkill(id: string): Promise<TrashEntry> {
+  return invoke<TrashEntry>("restore_skill", { id });
+}
+
+export async function emptyTrash(): Promise<number> {
+  return invoke<number>("empty_trash");
+}
+
+// === Snapshots ===
+
+export async function createSnapshot(
+  agent: string,
+  global: boolean,
+  projectPath?: string,
+  label?: string
+): Promise<SnapshotInfo> {
+  return invoke<SnapshotInfo>("create_snapshot", {
+    agent,
+    global,
+    projectPath: projectPath || null,
+    label: label || null,
+  });
+}
+
+export async function listSnapshots(agent?: string): Promise<SnapshotStatus[]> {
+  return invoke<SnapshotStatus[]>("list_snapshots", { agent: agent || null });
+}
+
+export async function restoreSnapshot(id: string): Promise<SnapshotInfo> {
+  return invoke<SnapshotInfo>("restore_snapshot", { id });
+}
+
+export async function deleteSnapshot(id: string): Promise<void> {
+  return invoke<void>("delete_snapshot", { id });
+}
+
 // === CLI Bridge operations ===
 
 export interface AddSkillOptions {
@@ -108,18 +230,68 @@ export async function cliInitSkill(name: string, path: string): Promise<CliOutpu
 
 // === Sandbox operations ===
 
-export async function createSandbox(skillPath: string): Promise<SandboxInfo> {
-  return invoke<SandboxInfo>("create_sandbox", { skillPath });
+export async function createSandbox(skillPath: string, options?: SandboxOptions): Promise<SandboxInfo> {
+  return invoke<SandboxInfo>("create_sandbox", { skillPath, options: options ?? null });
+}
+
+export async function runSandboxScript(
+  sandboxPath: string,
+  scriptName: string,
+  options?: RunOptions
+): Promise<ScriptOutput> {
+  return invoke<ScriptOutput>("run_sandbox_script", { sandboxPath, scriptName, options: options ?? null });
+}
+
+export async function runSandboxScriptStream(
+  runId: string,
+  sandboxPath: string,
+  scriptName: string,
+  options?: RunOptions
+): Promise<ScriptOutput> {
+  return invoke<ScriptOutput>("run_sandbox_script_stream", {
+    runId,
+    sandboxPath,
+    scriptName,
+    options: options ?? null,
+  });
 }
 
-export async function runSandboxScript(sandboxPath: string, scriptName: string): Promise<ScriptOutput> {
-  return invoke<ScriptOutput>("run_sandbox_script", { sandboxPath, scriptName });
+export async function writeSandboxStdin(runId: string, data: string): Promise<void> {
+  return invoke<void>("write_sandbox_stdin", { runId, data });
+}
+
+export async function closeSandboxStdin(runId: string): Promise<void> {
+  return invoke<void>("close_sandbox_stdin", { runId });
+}
+
+export async function killSandboxRun(runId: string): Promise<void> {
+  return invoke<void>("kill_sandbox_run", { runId });
+}
+
+export function onSandboxOutput(callback: (event: SandboxOutputEvent) => void): Promise<UnlistenFn> {
+  return listen<SandboxOutputEvent>("sandbox-output", (event) => {
+    callback(event.payload);
+  });
+}
+
+export async function readSandboxFile(sandboxPath: string, path: string): Promise<SandboxFile> {
+  return invoke<SandboxFile>("read_sandbox_file", { sandboxPath, path });
 }
 
 export async function cleanupSandbox(tempDirHandle: string): Promise<void> {
   return invoke<void>("cleanup_sandbox", { tempDirHandle });
 }
 
+export async function listSandboxes(): Promise<SandboxStatus[]> {
+  return invoke<SandboxStatus[]>("list_sandboxes");
+}
+
+// === Skill tests ===
+
+export async function runSkillTests(path: string): Promise<TestReport> {
+  return invoke<TestReport>("run_skill_tests", { path });
+}
+
 // === File watcher ===
 
 export async function watchSkillsDir(path: string): Promise<void> {
@@ -130,8 +302,37 @@ export async function unwatchSkillsDir(path: string): Promise<void> {
   return invoke<void>("unwatch_skills_dir", { path });
 }
 
-export function onSkillChanged(callback: (path: string) => void): Promise<UnlistenFn> {
-  return listen<string>("skill-changed", (event) => {
+export async function listWatches(): Promise<WatchInfo[]> {
+  return invoke<WatchInfo[]>("list_watches");
+}
+
+export function onSkillChanged(callback: (change: SkillChange) => void): Promise<UnlistenFn> {
+  return listen<SkillChange>("skill-changed", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onSkillsUpdated(callback: (update: SkillsUpdate) => void): Promise<UnlistenFn> {
+  return listen<SkillsUpdate>("skills-updated", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onSettingsChanged(callback: (settings: Settings) => void): Promise<UnlistenFn> {
+  return listen<Settings>("settings-changed", (event) => {
+    callback(event.payload);
+  });
+}
+
+/** Fires when the settings file was edited outside SkillDuck and cannot be read */
+export function onSettingsError(callback: (error: unknown) => void): Promise<UnlistenFn> {
+  return listen<unknown>("settings-error", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onOpenFileChanged(callback: (change: OpenFileChange) => void): Promise<UnlistenFn> {
+  return listen<OpenFileChange>("open-file-changed", (event) => {
     callback(event.payload);
   });
 }
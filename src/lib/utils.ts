@@ -1,6 +1,19 @@
 import { clsx, type ClassValue } from "clsx";
 import { twMerge } from "tailwind-merge";
+import type { CommandError } from "@/types/skills";
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+export function isCommandError(err: unknown): err is CommandError {
+  return typeof err === "object" && err !== null && "code" in err && "message" in err;
+}
+
+/** Turns a rejected Tauri command (or any thrown value) into a display string. */
+export function formatError(err: unknown): string {
+  // The message names what failed; the causes say why.
+  if (isCommandError(err)) return [err.message, ...(err.causes ?? [])].join(": ");
+  if (err instanceof Error) return err.message;
+  return String(err);
+}
@@ -1,8 +1,8 @@
 import { useCallback, useEffect, useState } from "react";
 import { useStore } from "@/hooks/useStore";
-import { detectAgents, getSettings } from "@/lib/tauri";
+import { detectAgents, getSettings, onSettingsChanged } from "@/lib/tauri";
 import type { AgentInfo, AgentProjectInfo } from "@/types/skills";
-import { cn } from "@/lib/utils";
+import { cn, formatError } from "@/lib/utils";
 import {
   AlertTriangle,
   Bot,
@@ -50,6 +50,10 @@ export function AgentsPage() {
 
   useEffect(() => {
     getSettings().then((s) => setHasScanRoots(s.scan_roots.length > 0)).catch(() => setHasScanRoots(false));
+    const unlisten = onSettingsChanged((s) => setHasScanRoots(s.scan_roots.length > 0));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   const loadAgents = useCallback(async () => {
@@ -72,7 +76,7 @@ export function AgentsPage() {
       const detected = result.filter((a) => a.detected).length;
       appendCliOutput(`Detected ${detected}/${result.length} agents`);
     } catch (err) {
-      appendCliOutput(`Error detecting agents: ${err}`);
+      appendCliOutput(`Error detecting agents: ${formatError(err)}`);
     } finally {
       setAgentsScanning(false);
       setAgentsScanned(true);
@@ -1,8 +1,8 @@
 import { useState, useCallback, useEffect } from "react";
 import { useStore } from "@/hooks/useStore";
-import { saveSkillMd, readSkillMd, cliInitSkill } from "@/lib/tauri";
-import type { ValidationResult, DiagnosticItem } from "@/types/skills";
-import { cn } from "@/lib/utils";
+import { saveSkillMd, readSkillMd, closeSkillMd, onOpenFileChanged, listFileHistory, restoreFileVersion, cliInitSkill } from "@/lib/tauri";
+import type { ValidationResult, DiagnosticItem, FileVersion, EditConflict, SkillDocument, HistoryEntry } from "@/types/skills";
+import { cn, formatError, isCommandError } from "@/lib/utils";
 import ReactMarkdown from "react-markdown";
 import remarkGfm from "remark-gfm";
 import {
@@ -17,6 +17,8 @@ import {
   Loader2,
   Info,
   Hash,
+  RefreshCw,
+  History,
 } from "lucide-react";
 
 interface FrontmatterFields {
@@ -52,6 +54,10 @@ export function EditorPage() {
   const [filePath, setFilePath] = useState("");
   const [saving, setSaving] = useState(false);
   const [dirty, setDirty] = useState(false);
+  const [version, setVersion] = useState<FileVersion | null>(null);
+  const [conflict, setConflict] = useState<EditConflict | null>(null);
+  const [diskChanged, setDiskChanged] = useState(false);
+  const [history, setHistory] = useState<HistoryEntry[] | null>(null);
   const [showNewDialog, setShowNewDialog] = useState(false);
   const [newSkillName, setNewSkillName] = useState("");
   const [newSkillPath, setNewSkillPath] = useState("");
@@ -120,7 +126,7 @@ export function EditorPage() {
     return () => clearTimeout(timeout);
   }, [runValidation]);
 
-  const handleSave = async () => {
+  const handleSave = async (overwrite = false) => {
     if (!filePath) return;
     setSaving(true);
     setIsLoading(true);
@@ -133,37 +139,107 @@ export function EditorPage() {
       if (fields.compatibility) frontmatter.compatibility = fields.compatibility;
       if (fields.allowed_tools) frontmatter.allowed_tools = fields.allowed_tools;
 
-      await saveSkillMd(filePath, frontmatter, body);
+      // Overwriting a conflict saves over whatever is on disk now.
+      const expected = overwrite ? conflict?.actual ?? null : version;
+      setVersion(await saveSkillMd(filePath, frontmatter, body, expected));
       setDirty(false);
+      setConflict(null);
+      setDiskChanged(false);
       appendCliOutput(`Saved ${filePath}`);
     } catch (err) {
-      appendCliOutput(`Error saving: ${err}`);
+      if (isCommandError(err) && err.code === "conflict") {
+        setConflict(err.details as EditConflict);
+      }
+      appendCliOutput(`Error saving: ${formatError(err)}`);
     } finally {
       setSaving(false);
       setIsLoading(false);
     }
   };
 
+  const applyDocument = useCallback((path: string, result: SkillDocument) => {
+    setFields({
+      name: result.frontmatter.name || "",
+      description: result.frontmatter.description || "",
+      license: result.frontmatter.license || "",
+      compatibility: result.frontmatter.compatibility || "",
+      allowed_tools: result.frontmatter.allowed_tools || "",
+      metadata_author: result.frontmatter["metadata.author"] || "",
+      metadata_version: result.frontmatter["metadata.version"] || "",
+    });
+    setBody(result.body);
+    setFilePath(path);
+    setVersion(result.version ?? null);
+    setDirty(false);
+    setConflict(null);
+    setDiskChanged(false);
+    setHistory(null);
+  }, []);
+
+  const handleReload = useCallback(async () => {
+    if (!filePath) return;
+    try {
+      applyDocument(filePath, await readSkillMd(filePath));
+      appendCliOutput(`Reloaded ${filePath}`);
+    } catch (err) {
+      appendCliOutput(`Error reloading: ${formatError(err)}`);
+    }
+  }, [filePath, applyDocument, appendCliOutput]);
+
+  // Pick up edits made outside the editor; ask before discarding our own.
+  useEffect(() => {
+    if (!filePath) return;
+    const unlisten = onOpenFileChanged((change) => {
+      if (change.path !== filePath) return;
+      if (dirty) {
+        setDiskChanged(true);
+      } else if (change.version) {
+        handleReload();
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [filePath, dirty, handleReload]);
+
+  useEffect(() => {
+    if (!filePath) return;
+    return () => {
+      closeSkillMd(filePath).catch(() => {});
+    };
+  }, [filePath]);
+
+  const toggleHistory = async () => {
+    if (history) {
+      setHistory(null);
+      return;
+    }
+    try {
+      setHistory(await listFileHistory(filePath));
+    } catch (err) {
+      appendCliOutput(`Error loading history: ${formatError(err)}`);
+    }
+  };
+
+  const handleRestore = async (entry: HistoryEntry) => {
+    if (dirty && !confirm("Discard unsaved changes and restore this version?")) return;
+    try {
+      await restoreFileVersion(filePath, entry.id);
+      appendCliOutput(`Restored ${filePath} to the version from ${new Date(entry.saved_at).toLocaleString()}`);
+      await handleReload();
+    } catch (err) {
+      appendCliOutput(`Error restoring: ${formatError(err)}`);
+    }
+  };
+
   const handleOpen = async () => {
     const path = prompt("Enter path to SKILL.md:");
     if (!path) return;
     try {
-      const result = await readSkillMd(path);
-      setFields({
-        name: result.frontmatter.name || "",
-        description: result.frontmatter.description || "",
-        license: result.frontmatter.license || "",
-        compatibility: result.frontmatter.compatibility || "",
-        allowed_tools: result.frontmatter.allowed_tools || "",
-        metadata_author: result.frontmatter["metadata.author"] || "",
-        metadata_version: result.frontmatter["metadata.version"] || "",
-      });
-      setBody(result.body);
-      setFilePath(path);
-      setDirty(false);
+      applyDocument(path, await readSkillMd(path));
       appendCliOutput(`Opened ${path}`);
     } catch (err) {
-      appendCliOutput(`Error opening: ${err}`);
+      appendCliOutput(`Error opening: ${formatError(err)}`);
     }
   };
 
@@ -176,7 +252,7 @@ export function EditorPage() {
       setNewSkillName("");
       setNewSkillPath("");
     } catch (err) {
-      appendCliOutput(`Error creating skill: ${err}`);
+      appendCliOutput(`Error creating skill: ${formatError(err)}`);
     }
   };
 
@@ -254,7 +330,18 @@ export function EditorPage() {
             Open
           </button>
           <button
-            onClick={handleSave}
+            onClick={toggleHistory}
+            disabled={!filePath}
+            className={cn(
+              "inline-flex items-center gap-1 rounded-md border px-2.5 py-1 text-xs hover:bg-accent transition-colors disabled:opacity-50",
+              history && "bg-accent"
+            )}
+          >
+            <History className="h-3 w-3" />
+            History
+          </button>
+          <button
+            onClick={() => handleSave()}
             disabled={saving || !filePath || !dirty}
             className="inline-flex items-center gap-1 rounded-md bg-primary px-2.5 py-1 text-xs text-primary-foreground hover:bg-primary/90 transition-colors disabled:opacity-50"
           >
@@ -264,6 +351,70 @@ export function EditorPage() {
         </div>
       </div>
 
+      {/* External changes */}
+      {(conflict || diskChanged) && (
+        <div className="border-b border-warning/20 bg-warning/10 px-4 py-2 space-y-2">
+          <div className="flex items-center justify-between">
+            <span className="inline-flex items-center gap-1.5 text-xs text-warning font-medium">
+              <AlertTriangle className="h-3.5 w-3.5" />
+              {conflict
+                ? conflict.actual
+                  ? "Not saved: the file changed on disk since it was opened"
+                  : "Not saved: the file was deleted since it was opened"
+                : "The file changed on disk"}
+            </span>
+            <div className="flex items-center gap-2">
+              <button
+                onClick={handleReload}
+                className="inline-flex items-center gap-1 rounded-md border px-2.5 py-1 text-xs hover:bg-accent transition-colors"
+              >
+                <RefreshCw className="h-3 w-3" />
+                Reload from disk
+              </button>
+              <button
+                onClick={() => (conflict ? handleSave(true) : setDiskChanged(false))}
+                disabled={saving}
+                className="inline-flex items-center gap-1 rounded-md bg-warning px-2.5 py-1 text-xs text-warning-foreground hover:bg-warning/90 transition-colors disabled:opacity-50"
+              >
+                <Save className="h-3 w-3" />
+                {conflict ? "Overwrite" : "Keep my changes"}
+              </button>
+            </div>
+          </div>
+          {conflict && (
+            <div className="grid grid-cols-2 gap-2">
+              <DiffView title="Your changes" diff={conflict.local_diff} />
+              <DiffView title="Changes on disk" diff={conflict.disk_diff} />
+            </div>
+          )}
+        </div>
+      )}
+
+      {/* Earlier versions */}
+      {history && (
+        <div className="border-b border-border px-4 py-2 max-h-40 overflow-y-auto">
+          {history.length === 0 ? (
+            <p className="text-xs text-muted-foreground">No earlier versions yet; one is kept each time the file is saved.</p>
+          ) : (
+            <ul className="space-y-1">
+              {history.map((entry) => (
+                <li key={entry.id} className="flex items-center justify-between text-xs">
+                  <span className="font-mono text-muted-foreground">
+                    {new Date(entry.saved_at).toLocaleString()} · {entry.size} bytes
+                  </span>
+                  <button
+                    onClick={() => handleRestore(entry)}
+                    className="rounded-md border px-2 py-0.5 text-[10px] hover:bg-accent transition-colors"
+                  >
+                    Restore
+                  </button>
+                </li>
+              ))}
+            </ul>
+          )}
+        </div>
+      )}
+
       {/* Main Editor Area */}
       <div className="flex flex-1 overflow-hidden">
         {/* Edit Panel */}
@@ -496,3 +647,26 @@ function FormField({
     </div>
   );
 }
+
+function DiffView({ title, diff }: { title: string; diff: string }) {
+  return (
+    <div className="min-w-0">
+      <h4 className="text-[10px] font-medium text-muted-foreground uppercase tracking-wider mb-1">{title}</h4>
+      <pre className="max-h-48 overflow-auto rounded-md border bg-background p-2 text-[10px] font-mono">
+        {diff
+          ? diff.split("\n").map((line, i) => (
+              <div
+                key={i}
+                className={cn(
+                  line.startsWith("+") && !line.startsWith("+++") && "text-success",
+                  line.startsWith("-") && !line.startsWith("---") && "text-destructive"
+                )}
+              >
+                {line || " "}
+              </div>
+            ))
+          : "No changes"}
+      </pre>
+    </div>
+  );
+}
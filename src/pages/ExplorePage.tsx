@@ -1,6 +1,7 @@
 import { useState, useEffect } from "react";
 import { searchSkills, type SkillSearchResult } from "@/lib/tauri";
 import { AddSkillDialog } from "@/components/skills/AddSkillDialog";
+import { formatError } from "@/lib/utils";
 import {
   Search,
   Download,
@@ -41,7 +42,7 @@ export function ExplorePage() {
       const response = await searchSkills(query, 20);
       setResults(response.skills);
     } catch (err) {
-      setError(`Failed to search: ${err}`);
+      setError(`Failed to search: ${formatError(err)}`);
       setResults([]);
     } finally {
       setSearching(false);
@@ -1,8 +1,8 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { useStore } from "@/hooks/useStore";
-import { createSandbox, runSandboxScript, cleanupSandbox } from "@/lib/tauri";
-import type { Skill, SandboxInfo, ScriptOutput } from "@/types/skills";
-import { cn } from "@/lib/utils";
+import { createSandbox, previewSkillContext, runSkillTests, runSandboxScriptStream, killSandboxRun, onSandboxOutput, cleanupSandbox } from "@/lib/tauri";
+import type { Skill, ContextPreview, SandboxInfo, ScriptOutput, SandboxOutputEvent, Interpreter } from "@/types/skills";
+import { cn, formatError } from "@/lib/utils";
 import ReactMarkdown from "react-markdown";
 import remarkGfm from "remark-gfm";
 import {
@@ -17,12 +17,15 @@ import {
   XCircle,
   Info,
   ChevronRight,
+  Square,
+  ListChecks,
 } from "lucide-react";
 
 type Stage = 1 | 2 | 3;
 
 export function SandboxPage() {
   const skills = useStore((s) => s.skills);
+  const agents = useStore((s) => s.agents);
   const appendCliOutput = useStore((s) => s.appendCliOutput);
   const setIsLoading = useStore((s) => s.setIsLoading);
 
@@ -32,17 +35,66 @@ export function SandboxPage() {
   const [scriptOutput, setScriptOutput] = useState<ScriptOutput | null>(null);
   const [loading, setLoading] = useState(false);
   const [scriptName, setScriptName] = useState("");
+  const [interpreter, setInterpreter] = useState<Interpreter | "">("");
+  const [runId, setRunId] = useState<string | null>(null);
+  const [liveOutput, setLiveOutput] = useState<SandboxOutputEvent[]>([]);
+  const [projectDir, setProjectDir] = useState("");
+  const [agentId, setAgentId] = useState("");
+  const [preview, setPreview] = useState<ContextPreview | null>(null);
+
+  useEffect(() => {
+    setPreview(null);
+    if (!selectedSkill || selectedSkill.agents.length === 0) return;
+    let cancelled = false;
+    previewSkillContext(selectedSkill.install_path, selectedSkill.agents[0], selectedSkill.project_root)
+      .then((result) => {
+        if (!cancelled) setPreview(result);
+      })
+      .catch((err) => appendCliOutput(`Error previewing context: ${formatError(err)}`));
+    return () => {
+      cancelled = true;
+    };
+  }, [selectedSkill, appendCliOutput]);
 
   const handleCreateSandbox = async () => {
     if (!selectedSkill) return;
     setLoading(true);
     setIsLoading(true);
     try {
-      const info = await createSandbox(selectedSkill.install_path);
+      const info = await createSandbox(selectedSkill.install_path, {
+        project_dir: projectDir.trim() || undefined,
+        seed_mode: "clone",
+        agent: agentId || undefined,
+      });
       setSandbox(info);
       appendCliOutput(`Sandbox created at ${info.path}`);
+      if (info.seed) {
+        appendCliOutput(`Copied ${info.seed.files} project files into ${info.workspace}`);
+      }
+    } catch (err) {
+      appendCliOutput(`Error creating sandbox: ${formatError(err)}`);
+    } finally {
+      setLoading(false);
+      setIsLoading(false);
+    }
+  };
+
+  const handleRunTests = async () => {
+    if (!selectedSkill) return;
+    setLoading(true);
+    setIsLoading(true);
+    try {
+      const report = await runSkillTests(selectedSkill.install_path);
+      for (const testCase of report.cases) {
+        appendCliOutput(`${testCase.passed ? "PASS" : "FAIL"} ${testCase.name} (${testCase.file})`);
+        for (const failure of testCase.failures) {
+          appendCliOutput(`  ${failure.message}`);
+          if (failure.diff) appendCliOutput(failure.diff);
+        }
+      }
+      appendCliOutput(`Tests: ${report.passed} passed, ${report.failed} failed (${report.duration_ms}ms)`);
     } catch (err) {
-      appendCliOutput(`Error creating sandbox: ${err}`);
+      appendCliOutput(`Error running tests: ${formatError(err)}`);
     } finally {
       setLoading(false);
       setIsLoading(false);
@@ -51,15 +103,31 @@ export function SandboxPage() {
 
   const handleRunScript = async () => {
     if (!sandbox || !scriptName) return;
+    const id = crypto.randomUUID();
     setLoading(true);
     setIsLoading(true);
+    setScriptOutput(null);
+    setLiveOutput([]);
+    setRunId(id);
+    const unlisten = await onSandboxOutput((event) => {
+      if (event.run_id === id) setLiveOutput((prev) => [...prev, event]);
+    });
     try {
-      const output = await runSandboxScript(sandbox.path, scriptName);
+      const output = await runSandboxScriptStream(id, sandbox.path, scriptName, {
+        interpreter: interpreter || undefined,
+      });
       setScriptOutput(output);
       appendCliOutput(`Script ${scriptName} exited with code ${output.exit_code} (${output.duration_ms}ms)`);
+      if (output.limit_hit) {
+        appendCliOutput(`Script ${scriptName} was stopped: ${output.limit_hit.replace("_", " ")} limit exceeded`);
+      } else if (output.killed) {
+        appendCliOutput(`Script ${scriptName} was stopped`);
+      }
     } catch (err) {
-      appendCliOutput(`Error running script: ${err}`);
+      appendCliOutput(`Error running script: ${formatError(err)}`);
     } finally {
+      unlisten();
+      setRunId(null);
       setLoading(false);
       setIsLoading(false);
     }
@@ -73,14 +141,16 @@ export function SandboxPage() {
       setScriptOutput(null);
       appendCliOutput("Sandbox cleaned up");
     } catch (err) {
-      appendCliOutput(`Error cleaning up: ${err}`);
+      appendCliOutput(`Error cleaning up: ${formatError(err)}`);
     }
   };
 
-  const metadataTokens = selectedSkill
-    ? Math.ceil((selectedSkill.name.length + selectedSkill.description.length) / 4)
-    : 0;
-  const bodyTokens = selectedSkill ? Math.ceil(selectedSkill.body.length / 4) : 0;
+  const metadataTokens = preview
+    ? preview.metadata_tokens
+    : selectedSkill
+      ? Math.ceil((selectedSkill.name.length + selectedSkill.description.length) / 4)
+      : 0;
+  const bodyTokens = preview ? preview.body_tokens : selectedSkill ? Math.ceil(selectedSkill.body.length / 4) : 0;
 
   return (
     <div className="flex h-full">
@@ -125,7 +195,11 @@ export function SandboxPage() {
             {([
               { stage: 1 as Stage, label: "Metadata Only", desc: `~${metadataTokens} tokens` },
               { stage: 2 as Stage, label: "Full Instructions", desc: `~${bodyTokens} tokens` },
-              { stage: 3 as Stage, label: "With Resources", desc: "All files" },
+              {
+                stage: 3 as Stage,
+                label: "With Resources",
+                desc: preview ? `~${preview.resource_tokens} tokens` : "All files",
+              },
             ]).map((item) => (
               <button
                 key={item.stage}
@@ -148,6 +222,26 @@ export function SandboxPage() {
 
         {/* Sandbox Controls */}
         <div className="p-4 space-y-2">
+          <input
+            type="text"
+            value={projectDir}
+            onChange={(e) => setProjectDir(e.target.value)}
+            placeholder="Project to copy (optional)"
+            className="w-full rounded-md border border-input bg-background px-2 py-1.5 text-[10px] font-mono placeholder:text-muted-foreground focus:outline-none focus:ring-1 focus:ring-ring"
+          />
+          <select
+            value={agentId}
+            onChange={(e) => setAgentId(e.target.value)}
+            title="Install the skill where this agent looks for project skills"
+            className="w-full rounded-md border border-input bg-background px-2 py-1.5 text-[10px] focus:outline-none focus:ring-1 focus:ring-ring"
+          >
+            <option value="">.agent/skills</option>
+            {agents.map((agent) => (
+              <option key={agent.id} value={agent.id}>
+                {agent.display_name}
+              </option>
+            ))}
+          </select>
           <button
             onClick={handleCreateSandbox}
             disabled={!selectedSkill || loading}
@@ -157,6 +251,15 @@ export function SandboxPage() {
             Create Sandbox
           </button>
 
+          <button
+            onClick={handleRunTests}
+            disabled={!selectedSkill || loading}
+            className="w-full inline-flex items-center justify-center gap-1.5 rounded-md border border-border px-3 py-2 text-xs hover:bg-accent disabled:opacity-50 transition-colors"
+          >
+            <ListChecks className="h-3 w-3" />
+            Run Tests
+          </button>
+
           {sandbox && (
             <>
               <div className="flex gap-2">
@@ -167,6 +270,18 @@ export function SandboxPage() {
                   placeholder="scripts/test.sh"
                   className="flex-1 rounded-md border border-input bg-background px-2 py-1.5 text-[10px] font-mono placeholder:text-muted-foreground focus:outline-none focus:ring-1 focus:ring-ring"
                 />
+                <select
+                  value={interpreter}
+                  onChange={(e) => setInterpreter(e.target.value as Interpreter | "")}
+                  title="Interpreter"
+                  className="rounded-md border border-input bg-background px-1 py-1.5 text-[10px] focus:outline-none focus:ring-1 focus:ring-ring"
+                >
+                  <option value="">direct</option>
+                  <option value="python3">python3</option>
+                  <option value="node">node</option>
+                  <option value="bash">bash</option>
+                  <option value="deno">deno</option>
+                </select>
                 <button
                   onClick={handleRunScript}
                   disabled={!scriptName || loading}
@@ -174,6 +289,15 @@ export function SandboxPage() {
                 >
                   <Terminal className="h-3 w-3" />
                 </button>
+                {runId && (
+                  <button
+                    onClick={() => killSandboxRun(runId).catch(() => {})}
+                    title="Stop script"
+                    className="rounded-md border border-destructive/20 text-destructive px-2 py-1.5 text-[10px] hover:bg-destructive/10"
+                  >
+                    <Square className="h-3 w-3" />
+                  </button>
+                )}
               </div>
               <button
                 onClick={handleCleanup}
@@ -206,6 +330,12 @@ export function SandboxPage() {
                 <Info className="h-3 w-3" />
                 Stage 1: Metadata (loaded at startup, ~{metadataTokens} tokens)
               </div>
+              {preview && (
+                <p className="text-[10px] text-muted-foreground mb-2">
+                  {preview.agent} loads {preview.totals.skills} skill(s) at startup, ~{preview.totals.metadata_tokens}{" "}
+                  tokens in total
+                </p>
+              )}
               <div className="space-y-1">
                 <div className="text-xs">
                   <span className="text-muted-foreground font-mono">name: </span>
@@ -238,9 +368,42 @@ export function SandboxPage() {
                   <ChevronRight className="h-3 w-3" />
                   Stage 3: Referenced Resources (loaded on demand)
                 </div>
-                <p className="text-xs text-muted-foreground">
-                  scripts/, references/, assets/ directories would be listed here.
-                </p>
+                {!preview || preview.resources.length === 0 ? (
+                  <p className="text-xs text-muted-foreground">No files are referenced from SKILL.md.</p>
+                ) : (
+                  preview.resources.map((resource) => (
+                    <details key={resource.path} className="mt-1 text-[11px]">
+                      <summary className={cn("cursor-pointer font-mono", !resource.exists && "text-destructive")}>
+                        {resource.path}{" "}
+                        <span className="text-muted-foreground">
+                          {resource.exists ? (resource.binary ? "binary" : `~${resource.tokens} tokens`) : "missing"}
+                        </span>
+                      </summary>
+                      {resource.content && (
+                        <pre className="font-mono bg-secondary/30 rounded p-2 mt-1 whitespace-pre-wrap max-h-40 overflow-y-auto">
+                          {resource.content}
+                        </pre>
+                      )}
+                    </details>
+                  ))
+                )}
+              </div>
+            )}
+
+            {/* Live Script Output */}
+            {runId && (
+              <div className="rounded-lg border bg-card p-4 mt-4">
+                <div className="flex items-center gap-2 mb-2">
+                  <Loader2 className="h-3.5 w-3.5 text-primary animate-spin" />
+                  <span className="text-xs font-medium">Running {scriptName}</span>
+                </div>
+                <pre className="text-[11px] font-mono bg-secondary/30 rounded p-3 whitespace-pre-wrap max-h-60 overflow-y-auto">
+                  {liveOutput.map((chunk, i) => (
+                    <span key={i} className={chunk.stream === "stderr" ? "text-destructive" : undefined}>
+                      {chunk.data}
+                    </span>
+                  ))}
+                </pre>
               </div>
             )}
 
@@ -255,8 +418,16 @@ export function SandboxPage() {
                   ) : (
                     <XCircle className="h-3.5 w-3.5 text-destructive" />
                   )}
-                  <span className="text-[10px] text-muted-foreground ml-auto">
-                    {scriptOutput.duration_ms}ms | exit: {scriptOutput.exit_code}
+                  <span
+                    className="text-[10px] text-muted-foreground ml-auto"
+                    title={scriptOutput.isolation.unavailable_reason}
+                  >
+                    {scriptOutput.duration_ms}ms | exit: {scriptOutput.exit_code} |{" "}
+                    {scriptOutput.isolation.backend === "none"
+                      ? "not isolated"
+                      : scriptOutput.isolation.pid_namespace
+                        ? "isolated"
+                        : "partially isolated"}
                   </span>
                 </div>
                 <pre className="text-[11px] font-mono bg-secondary/30 rounded p-3 whitespace-pre-wrap max-h-60 overflow-y-auto">
@@ -265,6 +436,20 @@ export function SandboxPage() {
                     <span className="text-destructive">{scriptOutput.stderr}</span>
                   )}
                 </pre>
+                {(["created", "modified", "deleted"] as const).map((kind) =>
+                  scriptOutput.changes[kind].map((change) => (
+                    <details key={`${kind}:${change.path}`} className="mt-2 text-[11px]">
+                      <summary className="cursor-pointer font-mono">
+                        <span className="text-muted-foreground">{kind}</span> {change.path}
+                      </summary>
+                      {change.diff && (
+                        <pre className="font-mono bg-secondary/30 rounded p-2 mt-1 whitespace-pre-wrap max-h-40 overflow-y-auto">
+                          {change.diff}
+                        </pre>
+                      )}
+                    </details>
+                  ))
+                )}
               </div>
             )}
 
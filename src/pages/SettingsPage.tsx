@@ -1,18 +1,39 @@
-import { useEffect, useState } from "react";
-import { getSettings, saveSettings } from "@/lib/tauri";
-import type { Settings } from "@/types/skills";
+import { useEffect, useRef, useState } from "react";
+import { getLegacyMigration, getSettings, getSettingsBackup, onSettingsChanged, onSettingsError, saveSettings } from "@/lib/tauri";
+import type { LegacyMove, Settings } from "@/types/skills";
 import { FolderOpen, Plus, Trash2, Save, Loader2 } from "lucide-react";
 import { open as openDialog } from "@tauri-apps/plugin-dialog";
-import { cn } from "@/lib/utils";
+import { cn, formatError } from "@/lib/utils";
 
 export function SettingsPage() {
   const [settings, setSettings] = useState<Settings | null>(null);
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const [message, setMessage] = useState<string | null>(null);
+  const [migration, setMigration] = useState<LegacyMove[]>([]);
+  // Unsaved edits, which a change from outside must not discard.
+  const dirty = useRef(false);
 
   useEffect(() => {
     loadSettings();
+    getLegacyMigration().then(setMigration).catch(() => setMigration([]));
+  }, []);
+
+  useEffect(() => {
+    const unlisten = onSettingsChanged((changed) => {
+      if (dirty.current) {
+        setMessage("Settings were changed outside SkillDuck; saving will overwrite those changes.");
+      } else {
+        setSettings(changed);
+      }
+    });
+    const unlistenError = onSettingsError((err) => {
+      setMessage(`${formatError(err)}. The previous settings stay in effect until the file is fixed.`);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+      unlistenError.then((fn) => fn());
+    };
   }, []);
 
   const loadSettings = async () => {
@@ -20,6 +41,11 @@ export function SettingsPage() {
     try {
       const result = await getSettings();
       setSettings(result);
+      dirty.current = false;
+      const backup = await getSettingsBackup();
+      if (backup) {
+        setMessage(`The settings file could not be read and was reset; the old file was moved to ${backup}`);
+      }
     } catch (err) {
       console.error("Failed to load settings:", err);
       setSettings({ scan_roots: [] });
@@ -32,12 +58,18 @@ export function SettingsPage() {
     if (!settings) return;
     setSaving(true);
     setMessage(null);
+    dirty.current = false;
     try {
-      await saveSettings(settings);
-      setMessage("Settings saved successfully!");
-      setTimeout(() => setMessage(null), 3000);
+      const warnings = await saveSettings(settings);
+      if (warnings.length > 0) {
+        setMessage(`Settings saved successfully, but: ${warnings.join("; ")}`);
+      } else {
+        setMessage("Settings saved successfully!");
+        setTimeout(() => setMessage(null), 3000);
+      }
     } catch (err) {
-      setMessage(`Failed to save settings: ${err}`);
+      dirty.current = true;
+      setMessage(`Failed to save settings: ${formatError(err)}`);
     } finally {
       setSaving(false);
     }
@@ -51,6 +83,7 @@ export function SettingsPage() {
     });
 
     if (selected && typeof selected === "string") {
+      dirty.current = true;
       setSettings((prev) => {
         if (!prev) return { scan_roots: [selected] };
         if (prev.scan_roots.includes(selected)) return prev;
@@ -60,6 +93,7 @@ export function SettingsPage() {
   };
 
   const removeScanRoot = (index: number) => {
+    dirty.current = true;
     setSettings((prev) => {
       if (!prev) return null;
       return {
@@ -69,6 +103,35 @@ export function SettingsPage() {
     });
   };
 
+  const addHomeRoot = async () => {
+    const selected = await openDialog({
+      directory: true,
+      multiple: false,
+      title: "Select home directory",
+    });
+
+    if (selected && typeof selected === "string") {
+      dirty.current = true;
+      setSettings((prev) => {
+        if (!prev) return { scan_roots: [], home_roots: [selected] };
+        const homeRoots = prev.home_roots ?? [];
+        if (homeRoots.includes(selected)) return prev;
+        return { ...prev, home_roots: [...homeRoots, selected] };
+      });
+    }
+  };
+
+  const removeHomeRoot = (index: number) => {
+    dirty.current = true;
+    setSettings((prev) => {
+      if (!prev) return null;
+      return {
+        ...prev,
+        home_roots: (prev.home_roots ?? []).filter((_, i) => i !== index),
+      };
+    });
+  };
+
   if (loading) {
     return (
       <div className="flex items-center justify-center h-full">
@@ -87,6 +150,20 @@ export function SettingsPage() {
       </div>
 
       <div className="space-y-6">
+        {migration.length > 0 && (
+          <div className="border rounded-lg p-4 space-y-1">
+            <h3 className="text-sm font-medium">Moved from ~/.skillduck</h3>
+            {migration.map((moved) => (
+              <p
+                key={moved.from}
+                className={cn("text-xs font-mono", moved.error ? "text-destructive" : "text-muted-foreground")}
+              >
+                {moved.error ? `Could not move ${moved.from}: ${moved.error}` : `${moved.from} → ${moved.to}`}
+              </p>
+            ))}
+          </div>
+        )}
+
         {/* Scan Roots Section */}
         <div className="border rounded-lg p-4">
           <div className="flex items-center justify-between mb-3">
@@ -132,6 +209,49 @@ export function SettingsPage() {
           )}
         </div>
 
+        {/* Home Roots Section */}
+        <div className="border rounded-lg p-4">
+          <div className="flex items-center justify-between mb-3">
+            <div>
+              <h3 className="text-sm font-medium">Other Home Directories</h3>
+              <p className="text-xs text-muted-foreground mt-0.5">
+                Homes of dev containers, mounted systems or CI users whose global skills are listed too
+              </p>
+            </div>
+            <button
+              onClick={addHomeRoot}
+              className="inline-flex items-center gap-1.5 rounded-md border px-3 py-1.5 text-xs hover:bg-accent transition-colors"
+            >
+              <Plus className="h-3 w-3" />
+              Add Directory
+            </button>
+          </div>
+
+          {settings?.home_roots && settings.home_roots.length > 0 ? (
+            <div className="space-y-2">
+              {settings.home_roots.map((root, index) => (
+                <div
+                  key={index}
+                  className="flex items-center gap-2 rounded border bg-card px-3 py-2"
+                >
+                  <FolderOpen className="h-4 w-4 text-muted-foreground" />
+                  <code className="flex-1 text-xs font-mono">{root}</code>
+                  <button
+                    onClick={() => removeHomeRoot(index)}
+                    className="p-1 rounded hover:bg-destructive/10 hover:text-destructive transition-colors"
+                  >
+                    <Trash2 className="h-3 w-3" />
+                  </button>
+                </div>
+              ))}
+            </div>
+          ) : (
+            <div className="text-center py-6 text-sm text-muted-foreground">
+              Only your own home directory is scanned for global skills.
+            </div>
+          )}
+        </div>
+
         {/* Save Button */}
         <div className="flex items-center justify-between pt-4 border-t">
           <div className="flex-1">
@@ -1,7 +1,7 @@
 import { useEffect, useState, useCallback } from "react";
 import { useStore } from "@/hooks/useStore";
 import { useUpdates } from "@/hooks/useUpdates";
-import { listSkills, cliRemoveSkill } from "@/lib/tauri";
+import { listSkills, removeSkill } from "@/lib/tauri";
 import { SkillCard } from "@/components/skills/SkillCard";
 import { SkillDetail } from "@/components/skills/SkillDetail";
 import { AddSkillDialog } from "@/components/skills/AddSkillDialog";
@@ -15,7 +15,7 @@ import {
   Loader2,
   ArrowUpCircle,
 } from "lucide-react";
-import { cn } from "@/lib/utils";
+import { cn, formatError } from "@/lib/utils";
 
 type ScopeFilter = "all" | "project" | "global";
 
@@ -36,15 +36,15 @@ export function SkillsPage() {
 
   const [showAddDialog, setShowAddDialog] = useState(false);
 
-  const loadSkills = useCallback(async () => {
+  const loadSkills = useCallback(async (refresh = false) => {
     setLoading(true);
     setIsLoading(true);
     try {
-      const result = await listSkills("all");
+      const result = await listSkills("all", refresh);
       setSkills(result);
       appendCliOutput(`Loaded ${result.length} skills`);
     } catch (err) {
-      appendCliOutput(`Error loading skills: ${err}`);
+      appendCliOutput(`Error loading skills: ${formatError(err)}`);
     } finally {
       setLoading(false);
       setIsLoading(false);
@@ -68,13 +68,12 @@ export function SkillsPage() {
     setIsLoading(true);
     try {
       const isGlobal = skill.scope === "global";
-      const result = await cliRemoveSkill([skill.name], skill.agents, isGlobal, skill.project_root);
-      appendCliOutput(result.stdout);
-      if (result.stderr) appendCliOutput(result.stderr);
+      const entry = await removeSkill(skill.name, skill.agents, isGlobal, skill.project_root);
+      appendCliOutput(`Moved ${entry.skill_name} to trash (${entry.items.length} location(s))`);
       setSelectedSkill(null);
       await loadSkills();
     } catch (err) {
-      appendCliOutput(`Error removing skill: ${err}`);
+      appendCliOutput(`Error removing skill: ${formatError(err)}`);
     } finally {
       setIsLoading(false);
     }
@@ -106,7 +105,7 @@ export function SkillsPage() {
                 <ArrowUpCircle className={cn("h-4 w-4", checkingUpdates && "animate-pulse")} />
               </button>
               <button
-                onClick={loadSkills}
+                onClick={() => loadSkills(true)}
                 disabled={loading}
                 className="rounded-md p-1.5 text-muted-foreground hover:bg-accent hover:text-foreground transition-colors"
                 title="Refresh"
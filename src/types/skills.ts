@@ -20,9 +20,13 @@ export interface Skill {
   has_update: boolean;
   body: string;
   project_root?: string;
+  /** Home root a global skill was found in, if not the current user's home */
+  home?: string;
 }
 
 export interface AgentGlobalInfo {
+  /** Home root this directory is in, if not the current user's home */
+  home?: string;
   path: string;
   skills: string[];
 }
@@ -38,11 +42,25 @@ export interface AgentInfo {
   display_name: string;
   detected: boolean;
   global: AgentGlobalInfo;
+  /** Global skills in the configured home roots the agent is found in */
+  other_homes: AgentGlobalInfo[];
   projects: AgentProjectInfo[];
 }
 
 export interface Settings {
+  /** Schema version; fields unknown to this build are kept when saving */
+  version?: number;
   scan_roots: string[];
+  /** Other homes (dev containers, CI users) whose global skills are listed */
+  home_roots?: string[];
+}
+
+/** An entry moved out of the ~/.skillduck used by older versions */
+export interface LegacyMove {
+  from: string;
+  to: string;
+  /** Set when the entry could not be moved and was left in place */
+  error: string | null;
 }
 
 export interface CliOutput {
@@ -51,6 +69,63 @@ export interface CliOutput {
   exit_code: number;
 }
 
+export interface SyncAction {
+  skill: string;
+  agent: string;
+  source: string;
+  destination: string;
+  status: "copied" | "planned" | "exists" | "shared" | "missing";
+}
+
+export interface SyncReport {
+  from_agent: string;
+  dry_run: boolean;
+  actions: SyncAction[];
+}
+
+export interface TrashedItem {
+  original_path: string;
+  agents: string[];
+  stored_as?: string;
+  symlink_target?: string;
+}
+
+export interface TrashEntry {
+  id: string;
+  skill_name: string;
+  agents: string[];
+  scope: "global" | "project";
+  project_root?: string;
+  removed_at: number;
+  items: TrashedItem[];
+}
+
+export interface SnapshotInfo {
+  id: string;
+  agent: string;
+  scope: "global" | "project";
+  project_path?: string;
+  skills_dir: string;
+  label?: string;
+  created_at: number;
+  size_bytes: number;
+  /** Skill directory (relative to skills_dir) to content hash */
+  skills: Record<string, string>;
+}
+
+export interface SnapshotDiff {
+  added: string[];
+  removed: string[];
+  modified: string[];
+  unchanged: number;
+}
+
+export interface SnapshotStatus extends SnapshotInfo {
+  /** null when the installed skills could not be read to compare */
+  diff: SnapshotDiff | null;
+  error?: string;
+}
+
 export interface ValidationResult {
   valid: boolean;
   errors: DiagnosticItem[];
@@ -63,9 +138,35 @@ export interface DiagnosticItem {
   severity: "error" | "warning";
 }
 
+export type SeedMode = "copy" | "clone";
+
+export interface SandboxOptions {
+  project_dir?: string;
+  seed_mode?: SeedMode;
+  agent?: string;
+}
+
+export interface SeedStats {
+  files: number;
+  cloned: number;
+  bytes: number;
+}
+
 export interface SandboxInfo {
+  id: string;
   path: string;
   temp_dir_handle: string;
+  workspace: string;
+  agent?: string;
+  project_dir?: string;
+  seed?: SeedStats;
+  created_at: number;
+  owner_pid: number;
+}
+
+export interface SandboxStatus extends SandboxInfo {
+  /** The app that created it has exited, or its directory is gone */
+  stale: boolean;
 }
 
 export interface ScriptOutput {
@@ -73,6 +174,199 @@ export interface ScriptOutput {
   stderr: string;
   exit_code: number;
   duration_ms: number;
+  isolation: IsolationReport;
+  /** The limit that stopped the script, if any */
+  limit_hit?: LimitKind;
+  /** Whether the run was stopped with kill_sandbox_run */
+  killed: boolean;
+  /** Files the script created, modified or deleted in the sandbox */
+  changes: FileChanges;
+}
+
+export interface FileChange {
+  /** Path relative to the sandbox root */
+  path: string;
+  size?: number;
+  previous_size?: number;
+  /** Unified diff, for small text files */
+  diff?: string;
+}
+
+export interface FileChanges {
+  created: FileChange[];
+  modified: FileChange[];
+  deleted: FileChange[];
+}
+
+export interface SandboxFile {
+  path: string;
+  size: number;
+  content: string;
+  binary: boolean;
+  truncated: boolean;
+}
+
+export interface SandboxOutputEvent {
+  run_id: string;
+  stream: "stdout" | "stderr";
+  data: string;
+}
+
+export type LimitKind = "wall_time" | "cpu_time" | "memory" | "processes" | "output";
+
+export interface ResourceLimits {
+  wall_time_ms?: number;
+  cpu_time_ms?: number;
+  memory_bytes?: number;
+  max_processes?: number;
+  max_output_bytes?: number;
+}
+
+export interface IsolationReport {
+  backend: "namespaces" | "none";
+  read_only_root: boolean;
+  writable_sandbox: boolean;
+  private_tmp: boolean;
+  network_disabled: boolean;
+  home_hidden: boolean;
+  fake_home: boolean;
+  pid_namespace: boolean;
+  unavailable_reason?: string;
+}
+
+export type Interpreter = "python3" | "node" | "bash" | "deno";
+
+export interface RunOptions {
+  /** Arguments passed to the script as-is (no shell interpolation) */
+  args?: string[];
+  /** Extra environment variables on top of HOME and PATH */
+  env?: Record<string, string>;
+  /** Written to the script's stdin when it starts */
+  stdin?: string;
+  /** Run the script with this interpreter instead of executing it directly */
+  interpreter?: Interpreter;
+  /** Keep network access inside the sandbox (disabled by default) */
+  allow_network?: boolean;
+  limits?: ResourceLimits;
+}
+
+export interface ResourcePreview {
+  /** Path relative to the skill directory, as referenced in the body */
+  path: string;
+  exists: boolean;
+  size: number;
+  tokens: number;
+  /** Absent for missing, binary or large files */
+  content?: string;
+  binary: boolean;
+}
+
+export interface AgentContextTotals {
+  skills: number;
+  /** Stage 1 for every installed skill, as loaded at startup */
+  available_skills: string;
+  metadata_tokens: number;
+  body_tokens: number;
+  resource_tokens: number;
+}
+
+export interface ContextPreview {
+  skill: string;
+  agent: string;
+  metadata: string;
+  metadata_tokens: number;
+  body: string;
+  body_tokens: number;
+  resources: ResourcePreview[];
+  resource_tokens: number;
+  totals: AgentContextTotals;
+}
+
+export interface TriggerMatch {
+  name: string;
+  install_path: string;
+  score: number;
+  /** Prompt terms found in the skill's name or description */
+  matched_terms: string[];
+}
+
+export interface SkillOverlap {
+  a: string;
+  b: string;
+  /** Cosine similarity of the two descriptions, 0 to 1 */
+  similarity: number;
+  shared_terms: string[];
+  /** Both skills match the prompt */
+  competing: boolean;
+}
+
+export interface TriggerReport {
+  prompt: string;
+  agent: string;
+  /** Best match first */
+  matches: TriggerMatch[];
+  /** The best two matches score too close to tell apart */
+  ambiguous: boolean;
+  overlaps: SkillOverlap[];
+}
+
+export interface TestFailure {
+  message: string;
+  /** Unified diff of expected and actual text */
+  diff?: string;
+}
+
+export interface TestCaseResult {
+  name: string;
+  /** Test file the case comes from, relative to the skill directory */
+  file: string;
+  passed: boolean;
+  duration_ms: number;
+  failures: TestFailure[];
+  exit_code?: number;
+  stdout: string;
+  stderr: string;
+}
+
+export interface TestReport {
+  skill: string;
+  path: string;
+  passed: number;
+  failed: number;
+  duration_ms: number;
+  cases: TestCaseResult[];
+}
+
+export type ChangeKind = "created" | "modified" | "removed" | "renamed";
+
+export interface SkillChange {
+  kind: ChangeKind;
+  /** The skill directory the file belongs to, or for a .skillduck.toml, the project directory */
+  skill_dir: string;
+  /** The affected file or directory */
+  path: string;
+  /** Previous path of a renamed file */
+  from?: string;
+}
+
+export interface SkillsUpdate {
+  added: Skill[];
+  removed: Skill[];
+  modified: Skill[];
+}
+
+export interface WatchInfo {
+  /** Canonical path of the watched directory */
+  path: string;
+  /** Agents whose global skills directory this is */
+  agents: string[];
+  scan_root: boolean;
+  /** Watched through watchSkillsDir */
+  manual: boolean;
+  /** A watched directory containing this one */
+  covered_by?: string;
+  /** Why the directory could not be watched */
+  error?: string;
 }
 
 export interface UpdateInfo {
@@ -88,3 +382,71 @@ export interface SkillLockEntry {
   installed_at: string;
   agents: string[];
 }
+
+export type ErrorCode =
+  | "not_found"
+  | "permission_denied"
+  | "already_exists"
+  | "io"
+  | "home_dir_unavailable"
+  | "invalid_skill"
+  | "parse_error"
+  | "invalid_input"
+  | "unknown_agent"
+  | "npx_missing"
+  | "spawn_failed"
+  | "not_executable"
+  | "watch_failed"
+  | "updater_failed"
+  | "conflict"
+  | "internal";
+
+/** Error payload rejected by every Tauri command. */
+export interface CommandError {
+  code: ErrorCode;
+  message: string;
+  path?: string;
+  causes?: string[];
+  /** Structured data for errors that need it, e.g. an EditConflict for "conflict" */
+  details?: unknown;
+}
+
+/** The content of a file at some point; versions with the same hash have the same content. */
+export interface FileVersion {
+  /** Milliseconds since the Unix epoch */
+  mtime: number;
+  hash: string;
+}
+
+export interface SkillDocument {
+  frontmatter: Record<string, string>;
+  body: string;
+  version?: FileVersion;
+}
+
+/** Details of a save refused because the file changed on disk since it was opened. */
+export interface EditConflict {
+  expected: FileVersion;
+  /** Absent if the file was deleted */
+  actual?: FileVersion;
+  /** Changes made in the editor, from the opened version */
+  local_diff: string;
+  /** Changes made on disk, from the opened version */
+  disk_diff: string;
+  disk_content?: string;
+}
+
+export interface OpenFileChange {
+  path: string;
+  /** Absent if the file was deleted */
+  version?: FileVersion;
+}
+
+/** A kept earlier version of an edited file. */
+export interface HistoryEntry {
+  id: string;
+  /** When the version was replaced, in milliseconds since the Unix epoch */
+  saved_at: number;
+  size: number;
+  hash: string;
+}